@@ -0,0 +1,248 @@
+//! Measures the effect of the packed evaluation of the gates of this crate.
+//!
+//! The `permutation_gate_evaluation` group compares the packed evaluation of
+//! `PermutationGate` with the path the prover took before it existed, i.e.
+//! lifting every point to the extension field and calling `eval_unfiltered`.
+//! The `sudoku_proving` group measures the time needed to prove a solution.
+//! The `sudoku_batch_proving` group measures the time needed to prove many
+//! solutions with a single proof of `SudokuBatchCircuit`. Its throughput is
+//! reported in puzzles per second, so that it can be compared with the one
+//! of single-puzzle proofs, i.e. the batch of size 1.
+//! The `sudoku_constraint_strategy` group compares the proving time of the
+//! backends of `SudokuCircuitBuilder`, and prints the size of their circuits.
+//! To compare it with another revision, run
+//!
+//! ``` text
+//! cargo bench --bench sudoku_proving -- --save-baseline before
+//! ```
+//!
+//! on that revision, and then `cargo bench --bench sudoku_proving -- --baseline before`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use plonky2::{
+    field::{
+        extension::{Extendable, FieldExtension},
+        types::Sample,
+    },
+    gates::gate::Gate,
+    hash::hash_types::HashOut,
+    iop::witness::PartialWitness,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+        vars::{EvaluationVars, EvaluationVarsBaseBatch},
+    },
+};
+use plonky2_playground::{
+    permutation::{DefaultSwapSchedule, PermutationGate, SwapSchedule},
+    sudoku::{
+        SudokuBatchCircuit, SudokuCircuitBuilder, SudokuConstraintStrategy, SudokuProblemTarget,
+        SudokuWitnessBuilder,
+    },
+};
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+type ExtensionField = <BaseField as Extendable<D>>::Extension;
+
+/// The number of points evaluated at once by the gate benchmarks.
+const BATCH_SIZE: usize = 1 << 12;
+
+fn bench_permutation_gate_evaluation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("permutation_gate_evaluation");
+
+    for n_objects in [4, 9] {
+        let gate = PermutationGate::try_new(
+            n_objects,
+            DefaultSwapSchedule::get_swap_sequence(n_objects),
+            true,
+        )
+        .expect("the default schedule is valid");
+        let num_wires = Gate::<BaseField, D>::num_wires(&gate);
+
+        // Wire `i` of point `j` is stored at index `i * BATCH_SIZE + j`.
+        let wires = BaseField::rand_vec(num_wires * BATCH_SIZE);
+        let public_inputs_hash = HashOut::rand();
+
+        group.bench_with_input(
+            BenchmarkId::new("extension_field", n_objects),
+            &gate,
+            |b, gate| {
+                b.iter(|| {
+                    Vec::from_iter((0..BATCH_SIZE).flat_map(|point| {
+                        let local_wires = Vec::from_iter((0..num_wires).map(|wire| {
+                            <ExtensionField as FieldExtension<D>>::from_basefield(
+                                wires[wire * BATCH_SIZE + point],
+                            )
+                        }));
+                        Gate::<BaseField, D>::eval_unfiltered(
+                            gate,
+                            EvaluationVars {
+                                local_constants: &[],
+                                local_wires: &local_wires,
+                                public_inputs_hash: &public_inputs_hash,
+                            },
+                        )
+                        .into_iter()
+                        .map(|constraint| {
+                            <ExtensionField as FieldExtension<D>>::to_basefield_array(&constraint)
+                                [0]
+                        })
+                    }))
+                })
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("packed", n_objects), &gate, |b, gate| {
+            b.iter(|| {
+                Gate::<BaseField, D>::eval_unfiltered_base_batch(
+                    gate,
+                    EvaluationVarsBaseBatch::new(BATCH_SIZE, &[], &wires, &public_inputs_hash),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn sudoku_circuit<const SIZE: usize, const SIZE_SQRT: usize>(
+    strategy: SudokuConstraintStrategy,
+) -> (
+    CircuitData<BaseField, PGConfig, D>,
+    SudokuProblemTarget<SIZE, SIZE_SQRT>,
+) {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let sudoku_target =
+        SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_strategy(
+            &mut builder,
+            strategy,
+        )
+        .expect("Circuit building goes wrong.");
+    (builder.build(), sudoku_target)
+}
+
+/// A valid solution for every Sudoku size: the `row`-th row is the
+/// `(row % SIZE_SQRT) * SIZE_SQRT + row / SIZE_SQRT`-th rotation of `1..=SIZE`.
+fn sudoku_solution<const SIZE: usize, const SIZE_SQRT: usize>() -> [[usize; SIZE]; SIZE] {
+    core::array::from_fn(|row| {
+        let shift = (row % SIZE_SQRT) * SIZE_SQRT + row / SIZE_SQRT;
+        core::array::from_fn(|col| (col + shift) % SIZE + 1)
+    })
+}
+
+fn bench_sudoku_proving_with_size<const SIZE: usize, const SIZE_SQRT: usize>(c: &mut Criterion) {
+    let (circuit, sudoku_target) =
+        sudoku_circuit::<SIZE, SIZE_SQRT>(SudokuConstraintStrategy::PermutationGate);
+    let solution = sudoku_solution::<SIZE, SIZE_SQRT>();
+    let problem = sudoku_problem(&solution);
+
+    let mut group = c.benchmark_group("sudoku_proving");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::from_parameter(format!("{SIZE}x{SIZE}")), |b| {
+        b.iter(|| {
+            let mut witness = PartialWitness::new();
+            SudokuWitnessBuilder::set_sudoku_witness(
+                &mut witness,
+                &sudoku_target,
+                problem,
+                solution,
+            );
+            circuit.prove(witness).expect("proof generation goes wrong")
+        })
+    });
+    group.finish();
+}
+
+/// The puzzle whose solution is proven: we only reveal the diagonal of the solution.
+fn sudoku_problem<const SIZE: usize>(solution: &[[usize; SIZE]; SIZE]) -> [[usize; SIZE]; SIZE] {
+    core::array::from_fn(|row| {
+        core::array::from_fn(|col| if row == col { solution[row][col] } else { 0 })
+    })
+}
+
+fn bench_sudoku_proving(c: &mut Criterion) {
+    bench_sudoku_proving_with_size::<4, 2>(c);
+    bench_sudoku_proving_with_size::<9, 3>(c);
+}
+
+fn bench_sudoku_batch_proving_with_size<
+    const SIZE: usize,
+    const SIZE_SQRT: usize,
+    const N: usize,
+>(
+    c: &mut Criterion,
+) {
+    let circuit =
+        SudokuBatchCircuit::<SIZE, SIZE_SQRT, N>::new(CircuitConfig::standard_recursion_config())
+            .expect("Circuit building goes wrong.");
+    let solution = sudoku_solution::<SIZE, SIZE_SQRT>();
+    let problem = sudoku_problem(&solution);
+
+    let mut group = c.benchmark_group("sudoku_batch_proving");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function(BenchmarkId::new(format!("{SIZE}x{SIZE}"), N), |b| {
+        b.iter(|| {
+            circuit
+                .prove([problem; N], [solution; N])
+                .expect("proof generation goes wrong")
+        })
+    });
+    group.finish();
+}
+
+fn bench_sudoku_batch_proving(c: &mut Criterion) {
+    bench_sudoku_batch_proving_with_size::<9, 3, 1>(c);
+    bench_sudoku_batch_proving_with_size::<9, 3, 4>(c);
+    bench_sudoku_batch_proving_with_size::<9, 3, 16>(c);
+}
+
+fn bench_sudoku_constraint_strategies(c: &mut Criterion) {
+    const SIZE: usize = 9;
+    const SIZE_SQRT: usize = 3;
+
+    let solution = sudoku_solution::<SIZE, SIZE_SQRT>();
+    let problem = sudoku_problem(&solution);
+
+    let mut group = c.benchmark_group("sudoku_constraint_strategy");
+    group.sample_size(10);
+    for strategy in [
+        SudokuConstraintStrategy::PermutationGate,
+        SudokuConstraintStrategy::LookupTable,
+    ] {
+        let (circuit, sudoku_target) = sudoku_circuit::<SIZE, SIZE_SQRT>(strategy);
+        println!(
+            "{strategy:?}: degree 2^{}, constraint degree {}, {} gate types",
+            circuit.common.degree_bits(),
+            circuit.common.constraint_degree(),
+            circuit.common.gates.len(),
+        );
+
+        group.bench_function(BenchmarkId::new(format!("{strategy:?}"), "9x9"), |b| {
+            b.iter(|| {
+                let mut witness = PartialWitness::new();
+                SudokuWitnessBuilder::set_sudoku_witness(
+                    &mut witness,
+                    &sudoku_target,
+                    problem,
+                    solution,
+                );
+                circuit.prove(witness).expect("proof generation goes wrong")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_permutation_gate_evaluation,
+    bench_sudoku_proving,
+    bench_sudoku_batch_proving,
+    bench_sudoku_constraint_strategies
+);
+criterion_main!(benches);
@@ -1,5 +1,13 @@
 use super::*;
 
+use crate::{
+    utilities::{
+        circuit_cost::CircuitCost,
+        metrics::{prove_with_metrics, Phase},
+    },
+    verifier_bundle::VerifierBundle,
+};
+
 use plonky2::{
     field::types::Field,
     iop::{
@@ -8,7 +16,7 @@ use plonky2::{
     },
     plonk::{
         circuit_builder::CircuitBuilder,
-        circuit_data::{CircuitConfig, CircuitData},
+        circuit_data::{CircuitConfig, CircuitData, CommonCircuitData, VerifierOnlyCircuitData},
     },
 };
 
@@ -66,7 +74,12 @@ impl RecursiveProdCircuitData {
         CircuitData<BaseField, PGConfig, D>,
         ProofWithPublicInputsTarget<D>,
     ) {
-        PRODUCT_CIRCUIT_DATA.get_or_init(|| self.product_circuit_data_constructor())
+        PRODUCT_CIRCUIT_DATA.get_or_init(|| {
+            crate::time_it! {
+                self.product_circuit_data_constructor();
+                Phase::CircuitBuild, "product/wrapper"
+            }
+        })
     }
 
     fn product_circuit_data_constructor(
@@ -154,27 +167,27 @@ impl RecursiveProdCircuitData {
             ),
         );
 
-        let mut proof = self
-            .recursive_circuit_data
-            .prove(witness)
-            .expect("proof generation goes wrong");
+        let mut proof =
+            prove_with_metrics(&self.recursive_circuit_data, witness, "product/base case")
+                .expect("proof generation goes wrong");
         while proof.public_inputs[self.remaining_factors_public_input_idx] != BaseField::ZERO {
             witness = PartialWitness::new();
 
             witness.set_proof_with_pis_target(&self.cyclic_proof_target, &proof);
 
-            proof = self
-                .recursive_circuit_data
-                .prove(witness)
-                .expect("proof generation goes wrong");
+            proof = prove_with_metrics(
+                &self.recursive_circuit_data,
+                witness,
+                "product/recursive step",
+            )
+            .expect("proof generation goes wrong");
         }
 
         let (product_circuit, proof_target) = self.product_circuit_data();
 
         let mut witness = PartialWitness::new();
-        witness.set_proof_with_pis_target(&proof_target, &proof);
-        product_circuit
-            .prove(witness)
+        witness.set_proof_with_pis_target(proof_target, &proof);
+        prove_with_metrics(product_circuit, witness, "product/wrapper")
             .expect("proof generation fails")
     }
 
@@ -199,6 +212,52 @@ impl RecursiveProdCircuitData {
             .0
             .verify(proof_with_public_inputs)
     }
+
+    /// The verifier-only data of the circuit that produces the proofs
+    /// returned by `prove`.
+    ///
+    /// Together with `common_data`, it is all a verifier needs
+    /// to check a proof.
+    pub fn verifier_only_data(&self) -> &VerifierOnlyCircuitData<PGConfig, D> {
+        &self.product_circuit_data().0.verifier_only
+    }
+
+    /// The common data of the circuit that produces the proofs
+    /// returned by `prove`.
+    pub fn common_data(&self) -> &CommonCircuitData<BaseField, D> {
+        &self.product_circuit_data().0.common
+    }
+
+    /// The verifier bundle of the circuit that produces the proofs
+    /// returned by `prove`.
+    ///
+    /// Proofs can be checked against it with
+    /// `crate::verifier_bundle::verify_with_bundle`, without having
+    /// to build the recursive circuit.
+    pub fn verifier_bundle(&self) -> VerifierBundle<BaseField, PGConfig, D> {
+        VerifierBundle::from_circuit_data(&self.product_circuit_data().0)
+    }
+
+    /// The verifier-only data of the cyclically recursive circuit, whose
+    /// proofs are wrapped by the circuit of `verifier_only_data`.
+    pub fn recursive_step_verifier_only_data(&self) -> &VerifierOnlyCircuitData<PGConfig, D> {
+        &self.recursive_circuit_data.verifier_only
+    }
+
+    /// The cost of the cyclically recursive circuit, which multiplies
+    /// a chunk of factors and verifies the proof of the previous chunks.
+    pub fn recursive_step_cost(&self) -> CircuitCost {
+        CircuitCost::of_circuit(&self.recursive_circuit_data)
+    }
+
+    /// Reads a proof produced by `prove`, serialized with
+    /// `ProofWithPublicInputs::to_bytes`.
+    pub fn proof_from_bytes(
+        &self,
+        bytes: Vec<u8>,
+    ) -> Result<ProofWithPublicInputs<BaseField, PGConfig, D>, anyhow::Error> {
+        ProofWithPublicInputs::from_bytes(bytes, self.common_data())
+    }
 }
 
 static RECURSIVE_PROD_CIRCUIT: OnceLock<RecursiveProdCircuitData> = OnceLock::new();
@@ -207,8 +266,12 @@ pub fn recursive_product_circuit() -> &'static RecursiveProdCircuitData {
     // needed for this procedure was determined experimentally.
     // At the moment I have no formal explanation of the reason why this works.
     const N_BOOTSTRAP_STEPS: usize = 2;
-    RECURSIVE_PROD_CIRCUIT
-        .get_or_init(|| build_recursive_product_circuit(N_BOOTSTRAP_STEPS, N_BOOTSTRAP_STEPS))
+    RECURSIVE_PROD_CIRCUIT.get_or_init(|| {
+        crate::time_it! {
+            build_recursive_product_circuit(N_BOOTSTRAP_STEPS, N_BOOTSTRAP_STEPS);
+            Phase::CircuitBuild, "product/recursive step"
+        }
+    })
 }
 
 fn build_recursive_product_circuit(
@@ -395,7 +458,7 @@ fn build_recursive_product_circuit(
     /// We will have to provide the position of some of the public input values
     /// for external entities. This function ensures that we cannot make indexing
     /// errors if the public inputs, or their ordering, change.
-    fn find_public_input_idx(public_inputs_vector: &Vec<Target>, target: Target) -> usize {
+    fn find_public_input_idx(public_inputs_vector: &[Target], target: Target) -> usize {
         public_inputs_vector
             .iter()
             .enumerate()
@@ -411,7 +474,7 @@ fn build_recursive_product_circuit(
     /// This function ensures that we cannot make indexing errors
     /// if the public inputs, or their order, change.
     fn find_inner_public_input(
-        public_inputs_vector: &Vec<Target>,
+        public_inputs_vector: &[Target],
         cyclic_proof: &ProofWithPublicInputsTarget<2>,
         target: Target,
     ) -> Target {
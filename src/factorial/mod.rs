@@ -1,9 +1,9 @@
 mod dynamic_size_product;
-pub use dynamic_size_product::recursive_product_circuit;
+pub use dynamic_size_product::{recursive_product_circuit, RecursiveProdCircuitData};
 
 mod static_size_product;
 pub use static_size_product::ConsecutiveProduct;
 
-const D: usize = 2;
-type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
-type BaseField = <PGConfig as plonky2::plonk::config::GenericConfig<D>>::F;
+pub const D: usize = 2;
+pub type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+pub type BaseField = <PGConfig as plonky2::plonk::config::GenericConfig<D>>::F;
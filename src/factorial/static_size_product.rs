@@ -85,7 +85,7 @@ impl ConsecutiveProduct {
     /// ```
     /// is stored.
     pub fn partial_product_target(&self, n_factors: usize) -> Option<Target> {
-        self.product_targets.get(n_factors).map(|t| t.clone())
+        self.product_targets.get(n_factors).copied()
     }
 
     /// Outputs the position where the circuit stores the product
@@ -105,10 +105,10 @@ impl ConsecutiveProduct {
     ///
     /// Equivalent to `self.partial_product_target(self.n_factors)`.
     pub fn final_product_target(&self) -> Target {
-        self.product_targets
+        *self
+            .product_targets
             .last()
             .expect("This vector is never empty")
-            .clone()
     }
 
     pub fn n_factors(&self) -> usize {
@@ -119,11 +119,16 @@ impl ConsecutiveProduct {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utilities::{
+        fixtures::{fixture_path, Fixture},
+        seeded_rng::test_rng,
+    };
     use plonky2::{
-        field::types::Field,
+        field::types::{Field, PrimeField64},
         iop::witness::{PartialWitness, WitnessWrite},
         plonk::{circuit_builder::CircuitBuilder, circuit_data::CircuitConfig},
     };
+    use rand::Rng;
 
     #[test]
     fn test_prod_1000() {
@@ -146,8 +151,9 @@ mod tests {
 
         let prod_circuit = circuit_builder.build::<PGConfig>();
 
+        let mut rng = test_rng();
         for _ in 0..N_TESTS {
-            let first_factor = BaseField::from_canonical_u16(rand::random());
+            let first_factor = BaseField::from_canonical_u16(rng.gen());
 
             let product_direct_computation = product_direct_computation(first_factor);
 
@@ -162,4 +168,58 @@ mod tests {
             assert_eq!(proof.public_inputs[0], product_direct_computation);
         }
     }
+
+    /// Proves the product of 32 consecutive numbers, starting from the one
+    /// stored in `fixtures/consecutive_product.txt`, and checks the public
+    /// inputs of the proof and the circuit digest against the fixture.
+    #[test]
+    fn test_consecutive_product_fixture() {
+        const N_FACTORS: usize = 32;
+
+        let path = fixture_path("consecutive_product.txt");
+        let stored_fixture = Fixture::read(&path).expect("the fixture is valid");
+        let first_factor = stored_fixture
+            .values("first_factor")
+            .expect("the fixture contains the first factor");
+
+        let mut circuit_builder =
+            CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+        let prod_targets = ConsecutiveProduct::new(&mut circuit_builder, N_FACTORS);
+        circuit_builder.register_public_input(prod_targets.final_product_target());
+        let prod_circuit = circuit_builder.build::<PGConfig>();
+
+        let mut witness = PartialWitness::new();
+        witness.set_target(
+            prod_targets.first_factor_target(),
+            BaseField::from_canonical_u64(first_factor[0]),
+        );
+        let proof = prod_circuit
+            .prove(witness)
+            .expect("proof generation goes wrong");
+
+        Fixture::new(
+            "The consecutive product circuit of `test_consecutive_product_fixture`.\n\
+            `first_factor` is the input, the other sections are checked.",
+        )
+        .section("first_factor", [first_factor])
+        .section(
+            "public_inputs",
+            [Vec::from_iter(
+                proof
+                    .public_inputs
+                    .iter()
+                    .map(|input| input.to_canonical_u64()),
+            )],
+        )
+        .section(
+            "circuit_digest",
+            [prod_circuit
+                .verifier_only
+                .circuit_digest
+                .elements
+                .map(|element| element.to_canonical_u64())],
+        )
+        .check(&path)
+        .expect("the circuit does not match the fixture");
+    }
 }
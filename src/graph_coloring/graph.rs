@@ -0,0 +1,97 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::{anyhow, bail, ensure, Context};
+
+/// An undirected graph without loops, whose vertices are `0..n_vertices`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    pub n_vertices: usize,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    /// Parses a graph in the DIMACS `.col` format, i.e.
+    ///
+    /// ``` text
+    /// c an optional comment
+    /// p edge <number of vertices> <number of edges>
+    /// e <vertex> <vertex>
+    /// ...
+    /// ```
+    ///
+    /// where vertices are numbered from `1`. Since many files list every edge
+    /// in both directions, duplicated edges are only kept once, and the number
+    /// of edges in the problem line is ignored.
+    pub fn from_dimacs(source: &str) -> anyhow::Result<Self> {
+        let mut n_vertices = None;
+        let mut edges = BTreeSet::new();
+
+        for (line_nr, line) in source.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            let parse_number = |token: Option<&str>| -> anyhow::Result<usize> {
+                token
+                    .ok_or_else(|| anyhow!("missing value"))?
+                    .parse()
+                    .map_err(|err| anyhow!("invalid number: {err}"))
+            };
+
+            match tokens.next() {
+                None | Some("c") => continue,
+                Some("p") => {
+                    ensure!(
+                        n_vertices.is_none(),
+                        "line {}: duplicated problem line",
+                        line_nr + 1
+                    );
+                    ensure!(
+                        matches!(tokens.next(), Some("edge" | "col")),
+                        "line {}: not a graph coloring problem",
+                        line_nr + 1
+                    );
+                    n_vertices = Some(
+                        parse_number(tokens.next())
+                            .with_context(|| format!("line {}", line_nr + 1))?,
+                    );
+                }
+                Some("e") => {
+                    let n_vertices = n_vertices.ok_or_else(|| {
+                        anyhow!("line {}: edge before the problem line", line_nr + 1)
+                    })?;
+                    let [u, v] = [(); 2].map(|_| parse_number(tokens.next()));
+                    let (u, v) = (u?, v?);
+                    ensure!(
+                        (1..=n_vertices).contains(&u) && (1..=n_vertices).contains(&v),
+                        "line {}: vertex out of range",
+                        line_nr + 1
+                    );
+                    ensure!(u != v, "line {}: loops cannot be colored", line_nr + 1);
+                    edges.insert((u.min(v) - 1, u.max(v) - 1));
+                }
+                Some(other) => bail!("line {}: unknown line type {other:?}", line_nr + 1),
+            }
+        }
+
+        Ok(Self {
+            n_vertices: n_vertices.ok_or_else(|| anyhow!("missing problem line"))?,
+            edges: Vec::from_iter(edges),
+        })
+    }
+
+    pub fn read_dimacs_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read the graph {}", path.display()))?;
+        Self::from_dimacs(&source)
+            .with_context(|| format!("{} is not a valid DIMACS graph", path.display()))
+    }
+
+    /// `neighbours[u]` lists the vertices adjacent to `u`.
+    pub fn neighbours(&self) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![vec![]; self.n_vertices];
+        for &(u, v) in self.edges.iter() {
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+        }
+        neighbours
+    }
+}
@@ -0,0 +1,84 @@
+use crate::utilities::{
+    connect_strategy::{ConnectStrategy, CopyConstraint},
+    random_access::large_random_access,
+};
+
+use super::GraphColoringTarget;
+
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+pub enum GraphColoringCircuitBuilder {}
+
+impl GraphColoringCircuitBuilder {
+    /// Proves the knowledge of a coloring with `n_colors` colors of the graph
+    /// whose `n_edges` edges are public inputs.
+    ///
+    /// Every color is enforced to be in `1..=n_colors`, and the colors of
+    /// the endpoints of every edge are looked up and enforced to be different,
+    /// by proving that their difference is invertible.
+    ///
+    /// The endpoints must be in `0..n_vertices`. The circuit does not check it,
+    /// since the verifier knows the graph anyway.
+    pub fn add_proof_of_graph_coloring<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+        n_vertices: usize,
+        n_edges: usize,
+        n_colors: usize,
+    ) -> Result<GraphColoringTarget, ()> {
+        Self::add_proof_of_graph_coloring_with_connect_strategy(
+            builder,
+            n_vertices,
+            n_edges,
+            n_colors,
+            CopyConstraint,
+        )
+    }
+
+    /// Like `add_proof_of_graph_coloring`, but the colors are enforced to be
+    /// in `1..=n_colors`, and the endpoints of every edge to have different
+    /// colors, through `connect_strategy`.
+    pub fn add_proof_of_graph_coloring_with_connect_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        n_vertices: usize,
+        n_edges: usize,
+        n_colors: usize,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<GraphColoringTarget, ()> {
+        // We cannot look up the colors of the endpoints in an empty table.
+        if n_vertices == 0 && n_edges > 0 {
+            return Err(());
+        }
+
+        let out = GraphColoringTarget {
+            edges: Vec::from_iter((0..n_edges).map(|_| builder.add_virtual_public_input_arr())),
+            colors: builder.add_virtual_targets(n_vertices),
+            n_colors,
+        };
+
+        // `color` is in `1..=n_colors` if and only if
+        // `(color - 1) * (color - 2) * ... * (color - n_colors) == 0`.
+        for &color in out.colors.iter() {
+            let factors = Vec::from_iter((1..=n_colors).map(|allowed_color| {
+                let allowed_color = builder.constant(F::from_canonical_usize(allowed_color));
+                builder.sub(color, allowed_color)
+            }));
+            let product = builder.mul_many(factors);
+            connect_strategy.assert_zero(builder, product);
+        }
+
+        for edge in out.edges.iter() {
+            let [color_u, color_v] =
+                edge.map(|vertex| large_random_access(builder, vertex, out.colors.clone()));
+            let difference = builder.sub(color_u, color_v);
+            connect_strategy.assert_nonzero(builder, difference);
+        }
+
+        Ok(out)
+    }
+}
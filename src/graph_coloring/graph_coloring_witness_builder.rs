@@ -0,0 +1,107 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::{PartialWitness, WitnessWrite},
+};
+
+use super::{Graph, GraphColoringTarget};
+
+pub enum GraphColoringWitnessBuilder {}
+
+impl GraphColoringWitnessBuilder {
+    /// Sets the edges of `graph` and the colors of its vertices.
+    ///
+    /// Fails if the size of `graph` or the length of `coloring` do not match
+    /// the circuit. If a color is not in `1..=n_colors`, or two adjacent
+    /// vertices share a color, no valid proof can be generated.
+    pub fn set_graph_coloring_witness<F: Field>(
+        witness: &mut PartialWitness<F>,
+        coloring_target: &GraphColoringTarget,
+        graph: &Graph,
+        coloring: &[usize],
+    ) -> Result<(), ()> {
+        if graph.n_vertices != coloring_target.n_vertices()
+            || graph.edges.len() != coloring_target.edges.len()
+            || coloring.len() != graph.n_vertices
+        {
+            return Err(());
+        }
+
+        for (targets, (u, v)) in coloring_target.edges.iter().zip(graph.edges.iter()) {
+            witness.set_target_arr(targets, &[u, v].map(|&n| F::from_canonical_usize(n)));
+        }
+        for (target, &color) in coloring_target.colors.iter().zip(coloring) {
+            witness.set_target(*target, F::from_canonical_usize(color));
+        }
+
+        Ok(())
+    }
+
+    /// Colors `graph` greedily, visiting the vertices by decreasing degree
+    /// and giving each one the smallest color not used by its neighbours.
+    ///
+    /// The coloring is always valid, but it may use more colors than needed.
+    /// Colors start from `1`.
+    pub fn greedy_coloring(graph: &Graph) -> Vec<usize> {
+        let neighbours = graph.neighbours();
+        let mut coloring = vec![0; graph.n_vertices];
+
+        for vertex in Self::vertices_by_degree(&neighbours) {
+            let mut color = 1;
+            while neighbours[vertex].iter().any(|&n| coloring[n] == color) {
+                color += 1;
+            }
+            coloring[vertex] = color;
+        }
+
+        coloring
+    }
+
+    /// Looks for a coloring of `graph` with at most `n_colors` colors,
+    /// in the format expected by `set_graph_coloring_witness`.
+    ///
+    /// The vertices are colored in the order of `greedy_coloring`, going back
+    /// to the previous vertex whenever all the colors clash with the
+    /// neighbours, so proving that no coloring exists can take up to
+    /// `n_colors^n_vertices` steps.
+    pub fn find_coloring(graph: &Graph, n_colors: usize) -> Option<Vec<usize>> {
+        let neighbours = graph.neighbours();
+        let order = Self::vertices_by_degree(&neighbours);
+        let mut coloring = vec![0; graph.n_vertices];
+
+        Self::extend_coloring(&neighbours, &order, n_colors, &mut coloring).then_some(coloring)
+    }
+
+    // Tries to color the vertices in `order`, given that the ones
+    // that come before them have already been colored.
+    fn extend_coloring(
+        neighbours: &[Vec<usize>],
+        order: &[usize],
+        n_colors: usize,
+        coloring: &mut [usize],
+    ) -> bool {
+        let Some((&vertex, other_vertices)) = order.split_first() else {
+            return true;
+        };
+
+        for color in 1..=n_colors {
+            if neighbours[vertex].iter().any(|&n| coloring[n] == color) {
+                continue;
+            }
+            coloring[vertex] = color;
+            if Self::extend_coloring(neighbours, other_vertices, n_colors, coloring) {
+                return true;
+            }
+        }
+        coloring[vertex] = 0;
+
+        false
+    }
+
+    // Vertices with many neighbours are the hardest to color,
+    // so it is better to color them first.
+    fn vertices_by_degree(neighbours: &[Vec<usize>]) -> Vec<usize> {
+        let mut vertices = Vec::from_iter(0..neighbours.len());
+        vertices.sort_by_key(|&vertex| std::cmp::Reverse(neighbours[vertex].len()));
+        vertices
+    }
+}
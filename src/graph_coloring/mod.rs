@@ -0,0 +1,38 @@
+use plonky2::iop::target::Target;
+
+mod graph;
+pub use graph::Graph;
+
+mod graph_coloring_circuit_builder;
+pub use graph_coloring_circuit_builder::GraphColoringCircuitBuilder;
+
+mod graph_coloring_witness_builder;
+pub use graph_coloring_witness_builder::GraphColoringWitnessBuilder;
+
+#[cfg(test)]
+mod tests;
+
+/// The targets of a proof of knowledge of a coloring with `n_colors` colors
+/// of a graph with `n_vertices` vertices and `edges.len()` edges.
+///
+/// The endpoints of the edges are public inputs, in order.
+/// The color of each vertex, in `1..=n_colors`, stays private.
+pub struct GraphColoringTarget {
+    edges: Vec<[Target; 2]>,
+    colors: Vec<Target>,
+    n_colors: usize,
+}
+
+impl GraphColoringTarget {
+    pub fn edges(&self) -> &[[Target; 2]] {
+        &self.edges
+    }
+
+    pub fn n_vertices(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn n_colors(&self) -> usize {
+        self.n_colors
+    }
+}
@@ -0,0 +1,173 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::PartialWitness,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+    },
+};
+
+use super::{Graph, GraphColoringCircuitBuilder, GraphColoringTarget, GraphColoringWitnessBuilder};
+use crate::utilities::{
+    connect_strategy::{ConnectStrategy, CopyConstraint},
+    test_connect_gate::StandardTestEq,
+};
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+/// The Mycielski graph of the 5-cycle, from the DIMACS coloring benchmarks.
+/// Its chromatic number is 4.
+const MYCIEL3: &str = "c FILE: myciel3.col
+c SOURCE: Michael Trick (trick@cmu.edu)
+p edge 11 20
+e 1 2
+e 1 4
+e 1 7
+e 1 9
+e 2 3
+e 2 6
+e 2 8
+e 3 5
+e 3 7
+e 3 10
+e 4 5
+e 4 6
+e 4 10
+e 5 8
+e 5 9
+e 6 11
+e 7 11
+e 8 11
+e 9 11
+e 10 11
+";
+
+fn circuit(
+    graph: &Graph,
+    n_colors: usize,
+    connect_strategy: impl ConnectStrategy,
+) -> (CircuitData<BaseField, PGConfig, D>, GraphColoringTarget) {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let coloring_target =
+        GraphColoringCircuitBuilder::add_proof_of_graph_coloring_with_connect_strategy(
+            &mut builder,
+            graph.n_vertices,
+            graph.edges.len(),
+            n_colors,
+            connect_strategy,
+        )
+        .expect("Circuit building goes wrong.");
+    (builder.build(), coloring_target)
+}
+
+#[test]
+fn test_dimacs_parsing() {
+    let graph = Graph::from_dimacs(MYCIEL3).expect("the graph is valid");
+    assert_eq!(graph.n_vertices, 11);
+    assert_eq!(graph.edges.len(), 20);
+    assert!(graph.edges.contains(&(9, 10)));
+
+    // Edges listed in both directions are kept once.
+    let graph =
+        Graph::from_dimacs("p edge 3 4\ne 1 2\ne 2 1\ne 2 3\ne 3 2\n").expect("the graph is valid");
+    assert_eq!(graph.edges, vec![(0, 1), (1, 2)]);
+
+    for invalid_graph in [
+        "e 1 2\n",
+        "p edge 2 1\ne 1 3\n",
+        "p edge 2 1\ne 1 1\n",
+        "p edge 2 1\ne 1 x\n",
+        "p edge 2 1\np edge 2 1\n",
+        "p edge 2 1\nx 1 2\n",
+    ] {
+        Graph::from_dimacs(invalid_graph).expect_err("an invalid graph is accepted");
+    }
+}
+
+#[test]
+fn test_graph_coloring() {
+    let graph = Graph::from_dimacs(MYCIEL3).expect("the graph is valid");
+
+    assert!(GraphColoringWitnessBuilder::find_coloring(&graph, 3).is_none());
+    let coloring =
+        GraphColoringWitnessBuilder::find_coloring(&graph, 4).expect("the graph is 4-colorable");
+    let greedy_coloring = GraphColoringWitnessBuilder::greedy_coloring(&graph);
+    for coloring in [&coloring, &greedy_coloring] {
+        assert!(graph.edges.iter().all(|&(u, v)| coloring[u] != coloring[v]));
+    }
+
+    let n_colors = *greedy_coloring
+        .iter()
+        .max()
+        .expect("the graph has vertices");
+    let (circuit, coloring_target) = circuit(&graph, n_colors.max(4), CopyConstraint);
+
+    for coloring in [coloring, greedy_coloring] {
+        let mut witness = PartialWitness::new();
+        GraphColoringWitnessBuilder::set_graph_coloring_witness(
+            &mut witness,
+            &coloring_target,
+            &graph,
+            &coloring,
+        )
+        .expect("the coloring matches the circuit");
+        let proof = circuit.prove(witness).expect("proof generation goes wrong");
+        assert_eq!(
+            proof.public_inputs,
+            Vec::from_iter(
+                graph
+                    .edges
+                    .iter()
+                    .flat_map(|&(u, v)| [u, v])
+                    .map(BaseField::from_canonical_usize)
+            )
+        );
+        circuit
+            .verify(proof)
+            .expect("proof verification goes wrong");
+    }
+}
+
+#[test]
+fn test_invalid_graph_coloring() {
+    let graph = Graph::from_dimacs(MYCIEL3).expect("the graph is valid");
+    // With `TestEq`, a witness that breaks the constraints still yields
+    // a proof, which the verifier must reject.
+    let (circuit, coloring_target) = circuit(&graph, 4, StandardTestEq::default());
+    let coloring =
+        GraphColoringWitnessBuilder::find_coloring(&graph, 4).expect("the graph is 4-colorable");
+
+    let (u, v) = graph.edges[0];
+    let mut same_color_on_edge = coloring.clone();
+    same_color_on_edge[u] = coloring[v];
+    let mut color_out_of_range = coloring.clone();
+    color_out_of_range[u] = 5;
+
+    for invalid_coloring in [same_color_on_edge, color_out_of_range] {
+        let mut witness = PartialWitness::new();
+        GraphColoringWitnessBuilder::set_graph_coloring_witness(
+            &mut witness,
+            &coloring_target,
+            &graph,
+            &invalid_coloring,
+        )
+        .expect("the coloring matches the circuit");
+        let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+        circuit
+            .verify(invalid_proof)
+            .expect_err("An invalid proof was accepted");
+    }
+
+    let mut witness = PartialWitness::<BaseField>::new();
+    GraphColoringWitnessBuilder::set_graph_coloring_witness(
+        &mut witness,
+        &coloring_target,
+        &graph,
+        &coloring[1..],
+    )
+    .expect_err("a coloring of the wrong length is accepted");
+}
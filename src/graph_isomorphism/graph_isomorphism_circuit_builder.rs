@@ -0,0 +1,82 @@
+use crate::{
+    permutation::ApplyPermutation,
+    utilities::connect_strategy::{ConnectStrategy, CopyConstraint},
+};
+
+use super::GraphIsomorphismTarget;
+
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+pub enum GraphIsomorphismCircuitBuilder<const N_VERTICES: usize> {}
+
+impl<const N_VERTICES: usize> GraphIsomorphismCircuitBuilder<N_VERTICES> {
+    /// Proves that the public graphs `graph_a` and `graph_b` are isomorphic,
+    /// without revealing the vertex mapping.
+    ///
+    /// Calling `σ` the permutation encoded by the swap selectors, we first
+    /// permute the rows of the adjacency matrix of `graph_a`, then its columns,
+    /// and we enforce the result to be the adjacency matrix of `graph_b`, i.e.
+    ///
+    /// ``` text
+    /// graph_b[i][j] == graph_a[σ(i)][σ(j)]
+    /// ```
+    ///
+    /// Directed graphs and graphs with loops are supported as well.
+    pub fn add_proof_of_graph_isomorphism<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<GraphIsomorphismTarget<N_VERTICES>, ()> {
+        Self::add_proof_of_graph_isomorphism_with_connect_strategy(builder, CopyConstraint)
+    }
+
+    /// Like `add_proof_of_graph_isomorphism`, but the permuted adjacency
+    /// matrix is enforced to be `graph_b` through `connect_strategy`.
+    pub fn add_proof_of_graph_isomorphism_with_connect_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<GraphIsomorphismTarget<N_VERTICES>, ()> {
+        let schedule_length = builder.permutation_swap_schedule_length(N_VERTICES);
+
+        let out = GraphIsomorphismTarget {
+            graph_a: core::array::from_fn(|_| builder.add_virtual_public_input_arr()),
+            graph_b: core::array::from_fn(|_| builder.add_virtual_public_input_arr()),
+            swap_selectors: builder.add_virtual_targets(schedule_length),
+        };
+
+        // Permuting the rows amounts to applying the permutation to every column.
+        let mut rows_permuted = [[builder.zero(); N_VERTICES]; N_VERTICES];
+        for col_idx in 0..N_VERTICES {
+            let column = out.graph_a.map(|row| row[col_idx]);
+            let permuted_column = builder.add_virtual_target_arr::<N_VERTICES>();
+            // The selectors are the same for every gate,
+            // so it is enough to check that they are boolean once.
+            builder.add_permutation_gate(
+                &column,
+                &out.swap_selectors,
+                &permuted_column,
+                col_idx == 0,
+            )?;
+            for (row, item) in rows_permuted.iter_mut().zip(permuted_column) {
+                row[col_idx] = item;
+            }
+        }
+
+        // Then we permute the columns, and the result must be `graph_b`.
+        for (row, graph_b_row) in rows_permuted.iter().zip(out.graph_b.iter()) {
+            builder.add_permutation_gate_with_connect_strategy(
+                row,
+                &out.swap_selectors,
+                graph_b_row,
+                false,
+                connect_strategy,
+            )?;
+        }
+
+        Ok(out)
+    }
+}
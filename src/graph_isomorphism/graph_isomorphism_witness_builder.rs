@@ -0,0 +1,101 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::{PartialWitness, WitnessWrite},
+};
+
+use crate::permutation::{DefaultSwapSchedule, SwapSchedule};
+
+use super::{AdjacencyMatrix, GraphIsomorphismTarget};
+
+pub enum GraphIsomorphismWitnessBuilder<const N_VERTICES: usize> {}
+
+impl<const N_VERTICES: usize> GraphIsomorphismWitnessBuilder<N_VERTICES> {
+    /// Sets the adjacency matrices of the two graphs, and the swap selectors
+    /// that encode `mapping`.
+    ///
+    /// `mapping` must be a permutation of `0..N_VERTICES`, that maps every
+    /// vertex of `graph_a` to the corresponding vertex of `graph_b`, i.e.
+    /// `graph_a[u][v] == graph_b[mapping[u]][mapping[v]]` for every `u` and `v`.
+    /// Otherwise, the rows and columns of `graph_a` permuted by the gates
+    /// differ from `graph_b`, and no valid proof can be generated.
+    pub fn set_graph_isomorphism_witness<F: Field>(
+        witness: &mut PartialWitness<F>,
+        isomorphism_target: &GraphIsomorphismTarget<N_VERTICES>,
+        graph_a: AdjacencyMatrix<N_VERTICES>,
+        graph_b: AdjacencyMatrix<N_VERTICES>,
+        mut mapping: [usize; N_VERTICES],
+    ) {
+        for (targets, graph) in [
+            (&isomorphism_target.graph_a, graph_a),
+            (&isomorphism_target.graph_b, graph_b),
+        ] {
+            for (row_targets, row_values) in targets.iter().zip(graph) {
+                witness.set_target_arr(row_targets, &row_values.map(F::from_bool));
+            }
+        }
+
+        // The selectors computed for `mapping` make the permutation gates
+        // output `items[mapping⁻¹(i)]` in the `i`-th position, which is
+        // exactly the permutation the circuit needs.
+        for (target, (selector, _idx1, _idx2)) in isomorphism_target.swap_selectors.iter().zip(
+            DefaultSwapSchedule::permutation_to_swap_schedule(&mut mapping),
+        ) {
+            witness.set_target(*target, F::from_bool(selector));
+        }
+    }
+
+    /// Looks for an isomorphism between `graph_a` and `graph_b`, in the format
+    /// expected by `set_graph_isomorphism_witness`.
+    ///
+    /// The vertices of `graph_a` are mapped in order, and a candidate image
+    /// is dropped as soon as an edge between mapped vertices is not
+    /// preserved. Graphs that are almost isomorphic can still require
+    /// trying most of the `N_VERTICES!` mappings.
+    pub fn find_isomorphism(
+        graph_a: &AdjacencyMatrix<N_VERTICES>,
+        graph_b: &AdjacencyMatrix<N_VERTICES>,
+    ) -> Option<[usize; N_VERTICES]> {
+        let mut mapping = [0; N_VERTICES];
+        let mut used = [false; N_VERTICES];
+        Self::extend_isomorphism(graph_a, graph_b, &mut mapping, &mut used, 0).then_some(mapping)
+    }
+
+    // Tries to extend the mapping of the vertices `0..n_mapped` of `graph_a`
+    // to all of its vertices.
+    fn extend_isomorphism(
+        graph_a: &AdjacencyMatrix<N_VERTICES>,
+        graph_b: &AdjacencyMatrix<N_VERTICES>,
+        mapping: &mut [usize; N_VERTICES],
+        used: &mut [bool; N_VERTICES],
+        n_mapped: usize,
+    ) -> bool {
+        if n_mapped == N_VERTICES {
+            return true;
+        }
+
+        let u = n_mapped;
+        for candidate in 0..N_VERTICES {
+            if used[candidate] {
+                continue;
+            }
+            mapping[u] = candidate;
+
+            // The edges between `u` and the vertices mapped so far,
+            // `u` included, must be preserved.
+            let is_consistent = (0..=u).all(|v| {
+                graph_a[u][v] == graph_b[candidate][mapping[v]]
+                    && graph_a[v][u] == graph_b[mapping[v]][candidate]
+            });
+
+            if is_consistent {
+                used[candidate] = true;
+                if Self::extend_isomorphism(graph_a, graph_b, mapping, used, n_mapped + 1) {
+                    return true;
+                }
+                used[candidate] = false;
+            }
+        }
+
+        false
+    }
+}
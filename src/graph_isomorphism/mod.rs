@@ -0,0 +1,36 @@
+use plonky2::iop::target::Target;
+
+mod graph_isomorphism_circuit_builder;
+pub use graph_isomorphism_circuit_builder::GraphIsomorphismCircuitBuilder;
+
+mod graph_isomorphism_witness_builder;
+pub use graph_isomorphism_witness_builder::GraphIsomorphismWitnessBuilder;
+
+#[cfg(test)]
+mod tests;
+
+/// An adjacency matrix: `graph[u][v]` is `true` if and only if
+/// there is an edge from the vertex `u` to the vertex `v`.
+pub type AdjacencyMatrix<const N_VERTICES: usize> = [[bool; N_VERTICES]; N_VERTICES];
+
+/// The targets of a proof that two graphs with `N_VERTICES` vertices
+/// are isomorphic.
+///
+/// Both adjacency matrices are public inputs, `graph_a` first, row by row.
+/// The vertex mapping is encoded by `swap_selectors`, which are shared by
+/// all the permutation gates of the circuit, and stays private.
+pub struct GraphIsomorphismTarget<const N_VERTICES: usize> {
+    graph_a: [[Target; N_VERTICES]; N_VERTICES],
+    graph_b: [[Target; N_VERTICES]; N_VERTICES],
+    swap_selectors: Vec<Target>,
+}
+
+impl<const N_VERTICES: usize> GraphIsomorphismTarget<N_VERTICES> {
+    pub fn graph_a(&self) -> &[[Target; N_VERTICES]; N_VERTICES] {
+        &self.graph_a
+    }
+
+    pub fn graph_b(&self) -> &[[Target; N_VERTICES]; N_VERTICES] {
+        &self.graph_b
+    }
+}
@@ -0,0 +1,144 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::PartialWitness,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+    },
+};
+
+use super::{
+    AdjacencyMatrix, GraphIsomorphismCircuitBuilder, GraphIsomorphismTarget,
+    GraphIsomorphismWitnessBuilder,
+};
+use crate::utilities::{
+    connect_strategy::{ConnectStrategy, CopyConstraint},
+    test_connect_gate::StandardTestEq,
+};
+
+const N_VERTICES: usize = 10;
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+fn undirected_graph(edges: &[(usize, usize)]) -> AdjacencyMatrix<N_VERTICES> {
+    let mut graph = [[false; N_VERTICES]; N_VERTICES];
+    for &(u, v) in edges {
+        graph[u][v] = true;
+        graph[v][u] = true;
+    }
+    graph
+}
+
+/// The Petersen graph: an outer 5-cycle, an inner pentagram, and
+/// the spokes between them.
+fn petersen_graph() -> AdjacencyMatrix<N_VERTICES> {
+    undirected_graph(&Vec::from_iter(
+        (0..5).flat_map(|i| [(i, (i + 1) % 5), (5 + i, 5 + (i + 2) % 5), (i, 5 + i)]),
+    ))
+}
+
+/// The pentagonal prism, which is 3-regular like the Petersen graph,
+/// but not isomorphic to it.
+fn prism_graph() -> AdjacencyMatrix<N_VERTICES> {
+    undirected_graph(&Vec::from_iter(
+        (0..5).flat_map(|i| [(i, (i + 1) % 5), (5 + i, 5 + (i + 1) % 5), (i, 5 + i)]),
+    ))
+}
+
+fn relabel(
+    graph: &AdjacencyMatrix<N_VERTICES>,
+    mapping: [usize; N_VERTICES],
+) -> AdjacencyMatrix<N_VERTICES> {
+    let mut relabeled = [[false; N_VERTICES]; N_VERTICES];
+    for u in 0..N_VERTICES {
+        for v in 0..N_VERTICES {
+            relabeled[mapping[u]][mapping[v]] = graph[u][v];
+        }
+    }
+    relabeled
+}
+
+fn circuit(
+    connect_strategy: impl ConnectStrategy,
+) -> (
+    CircuitData<BaseField, PGConfig, D>,
+    GraphIsomorphismTarget<N_VERTICES>,
+) {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let isomorphism_target =
+        GraphIsomorphismCircuitBuilder::<N_VERTICES>::add_proof_of_graph_isomorphism_with_connect_strategy(
+            &mut builder,
+            connect_strategy,
+        )
+        .expect("Circuit building goes wrong.");
+    (builder.build(), isomorphism_target)
+}
+
+#[test]
+fn test_isomorphic_graphs() {
+    let (circuit, isomorphism_target) = circuit(CopyConstraint);
+
+    let graph_a = petersen_graph();
+    let secret_mapping = [3, 7, 0, 9, 5, 1, 8, 2, 6, 4];
+    let graph_b = relabel(&graph_a, secret_mapping);
+
+    // The graph has many automorphisms, so the mapping we find
+    // is not necessarily the one we used.
+    let mapping = GraphIsomorphismWitnessBuilder::find_isomorphism(&graph_a, &graph_b)
+        .expect("the graphs are isomorphic");
+    assert_eq!(relabel(&graph_a, mapping), graph_b);
+
+    for mapping in [secret_mapping, mapping] {
+        let mut witness = PartialWitness::new();
+        GraphIsomorphismWitnessBuilder::set_graph_isomorphism_witness(
+            &mut witness,
+            &isomorphism_target,
+            graph_a,
+            graph_b,
+            mapping,
+        );
+        let proof = circuit.prove(witness).expect("proof generation goes wrong");
+        assert_eq!(
+            proof.public_inputs,
+            Vec::from_iter(
+                graph_a
+                    .iter()
+                    .chain(graph_b.iter())
+                    .flatten()
+                    .map(|&edge| BaseField::from_bool(edge))
+            )
+        );
+        circuit
+            .verify(proof)
+            .expect("proof verification goes wrong");
+    }
+}
+
+#[test]
+fn test_non_isomorphic_graphs() {
+    // With `TestEq`, a witness that breaks the constraints still yields
+    // a proof, which the verifier must reject.
+    let (circuit, isomorphism_target) = circuit(StandardTestEq::default());
+
+    let graph_a = petersen_graph();
+    let graph_b = prism_graph();
+    assert!(GraphIsomorphismWitnessBuilder::find_isomorphism(&graph_a, &graph_b).is_none());
+
+    // The permutation gates compute a different `graph_b` than the one we set.
+    let mut witness = PartialWitness::new();
+    GraphIsomorphismWitnessBuilder::set_graph_isomorphism_witness(
+        &mut witness,
+        &isomorphism_target,
+        graph_a,
+        graph_b,
+        core::array::from_fn(|idx| idx),
+    );
+    let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+    circuit
+        .verify(invalid_proof)
+        .expect_err("An invalid proof was accepted");
+}
@@ -0,0 +1,66 @@
+use crate::{
+    permutation::ApplyPermutation,
+    utilities::{
+        connect_strategy::{ConnectStrategy, CopyConstraint},
+        random_access::large_random_access,
+    },
+};
+
+use super::HamiltonianCycleTarget;
+
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+pub enum HamiltonianCycleCircuitBuilder<const N_VERTICES: usize> {}
+
+impl<const N_VERTICES: usize> HamiltonianCycleCircuitBuilder<N_VERTICES> {
+    /// Proves the knowledge of a Hamiltonian cycle in the public graph.
+    ///
+    /// The prover permutes the vertices `0..N_VERTICES` with a permutation
+    /// gate, obtaining the order in which the cycle visits them. Then, for
+    /// every pair of consecutive vertices in that order, and for the last
+    /// and the first one, we look up the adjacency matrix to check that
+    /// there is an edge between them.
+    ///
+    /// Edges are followed from `u` to `v` when `graph[u][v]` is set,
+    /// so directed graphs are supported as well.
+    pub fn add_proof_of_hamiltonian_cycle<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<HamiltonianCycleTarget<N_VERTICES>, ()> {
+        Self::add_proof_of_hamiltonian_cycle_with_connect_strategy(builder, CopyConstraint)
+    }
+
+    /// Like `add_proof_of_hamiltonian_cycle`, but the edges are enforced
+    /// to be in the graph through `connect_strategy`.
+    pub fn add_proof_of_hamiltonian_cycle_with_connect_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<HamiltonianCycleTarget<N_VERTICES>, ()> {
+        let schedule_length = builder.permutation_swap_schedule_length(N_VERTICES);
+
+        let out = HamiltonianCycleTarget {
+            graph: core::array::from_fn(|_| builder.add_virtual_public_input_arr()),
+            swap_selectors: builder.add_virtual_targets(schedule_length),
+        };
+
+        let cycle = builder.permutation_of_indices(N_VERTICES, &out.swap_selectors, true)?;
+
+        let adjacency_table = Vec::from_iter(out.graph.iter().flatten().copied());
+        let n_vertices = builder.constant(F::from_canonical_usize(N_VERTICES));
+
+        for (idx, &from) in cycle.iter().enumerate() {
+            let to = cycle[(idx + 1) % N_VERTICES];
+            // `graph[from][to]` is in position `from * N_VERTICES + to`.
+            let edge_idx = builder.mul_add(from, n_vertices, to);
+            let edge = large_random_access(builder, edge_idx, adjacency_table.clone());
+            connect_strategy.assert_one(builder, edge);
+        }
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,89 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::{PartialWitness, WitnessWrite},
+};
+
+use crate::permutation::{inverse_permutation, DefaultSwapSchedule, SwapSchedule};
+
+use super::HamiltonianCycleTarget;
+
+pub enum HamiltonianCycleWitnessBuilder<const N_VERTICES: usize> {}
+
+impl<const N_VERTICES: usize> HamiltonianCycleWitnessBuilder<N_VERTICES> {
+    /// Sets the adjacency matrix of the graph, and the swap selectors
+    /// that encode `cycle`.
+    ///
+    /// `cycle` lists the vertices in the order they are visited, so it must be
+    /// a permutation of `0..N_VERTICES`. If two consecutive vertices of
+    /// `cycle`, or the last and the first one, are not adjacent in `graph`,
+    /// the lookup of their edge outputs `0` instead of `1`, and no valid
+    /// proof can be generated.
+    pub fn set_hamiltonian_cycle_witness<F: Field>(
+        witness: &mut PartialWitness<F>,
+        cycle_target: &HamiltonianCycleTarget<N_VERTICES>,
+        graph: [[bool; N_VERTICES]; N_VERTICES],
+        cycle: [usize; N_VERTICES],
+    ) {
+        for (row_targets, row_values) in cycle_target.graph.iter().zip(graph) {
+            witness.set_target_arr(row_targets, &row_values.map(F::from_bool));
+        }
+
+        // Applying the swaps selected for a permutation outputs its inverse,
+        // so we have to select the swaps for the inverse of `cycle`.
+        let mut inverse_cycle = [0; N_VERTICES];
+        inverse_permutation(&cycle, &mut inverse_cycle);
+
+        for (target, (selector, _idx1, _idx2)) in cycle_target.swap_selectors.iter().zip(
+            DefaultSwapSchedule::permutation_to_swap_schedule(&mut inverse_cycle),
+        ) {
+            witness.set_target(*target, F::from_bool(selector));
+        }
+    }
+
+    /// Looks for a Hamiltonian cycle in `graph`, in the format expected
+    /// by `set_hamiltonian_cycle_witness`.
+    ///
+    /// Paths starting from `0` are extended depth-first until one of them
+    /// closes into a cycle, so on graphs without Hamiltonian cycles, like
+    /// the Petersen graph, every simple path from `0` is visited.
+    pub fn find_hamiltonian_cycle(
+        graph: &[[bool; N_VERTICES]; N_VERTICES],
+    ) -> Option<[usize; N_VERTICES]> {
+        if N_VERTICES == 0 {
+            return Some([0; N_VERTICES]);
+        }
+
+        // Every Hamiltonian cycle visits `0`, so we can start from there.
+        let mut cycle = [0; N_VERTICES];
+        let mut visited = [false; N_VERTICES];
+        visited[0] = true;
+        Self::extend_path(graph, &mut cycle, &mut visited, 1).then_some(cycle)
+    }
+
+    // Tries to extend the path `cycle[..path_len]` to a Hamiltonian cycle.
+    fn extend_path(
+        graph: &[[bool; N_VERTICES]; N_VERTICES],
+        cycle: &mut [usize; N_VERTICES],
+        visited: &mut [bool; N_VERTICES],
+        path_len: usize,
+    ) -> bool {
+        let last = cycle[path_len - 1];
+        if path_len == N_VERTICES {
+            return graph[last][cycle[0]];
+        }
+
+        for next in 0..N_VERTICES {
+            if visited[next] || !graph[last][next] {
+                continue;
+            }
+            cycle[path_len] = next;
+            visited[next] = true;
+            if Self::extend_path(graph, cycle, visited, path_len + 1) {
+                return true;
+            }
+            visited[next] = false;
+        }
+
+        false
+    }
+}
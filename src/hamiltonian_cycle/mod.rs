@@ -0,0 +1,27 @@
+use plonky2::iop::target::Target;
+
+mod hamiltonian_cycle_circuit_builder;
+pub use hamiltonian_cycle_circuit_builder::HamiltonianCycleCircuitBuilder;
+
+mod hamiltonian_cycle_witness_builder;
+pub use hamiltonian_cycle_witness_builder::HamiltonianCycleWitnessBuilder;
+
+#[cfg(test)]
+mod tests;
+
+/// The targets of a proof of knowledge of a Hamiltonian cycle
+/// in a graph with `N_VERTICES` vertices.
+///
+/// The adjacency matrix of the graph is public, row by row:
+/// `graph[u][v]` is `1` if there is an edge from `u` to `v`, `0` otherwise.
+/// The cycle is encoded by `swap_selectors`, and stays private.
+pub struct HamiltonianCycleTarget<const N_VERTICES: usize> {
+    graph: [[Target; N_VERTICES]; N_VERTICES],
+    swap_selectors: Vec<Target>,
+}
+
+impl<const N_VERTICES: usize> HamiltonianCycleTarget<N_VERTICES> {
+    pub fn graph(&self) -> &[[Target; N_VERTICES]; N_VERTICES] {
+        &self.graph
+    }
+}
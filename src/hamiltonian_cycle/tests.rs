@@ -0,0 +1,117 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::PartialWitness,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+    },
+};
+
+use super::{
+    HamiltonianCycleCircuitBuilder, HamiltonianCycleTarget, HamiltonianCycleWitnessBuilder,
+};
+use crate::utilities::{
+    connect_strategy::{ConnectStrategy, CopyConstraint},
+    test_connect_gate::StandardTestEq,
+};
+
+// The adjacency matrix has 100 entries, more than what a single
+// `RandomAccessGate` can handle with the standard configuration.
+const N_VERTICES: usize = 10;
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+fn undirected_graph(edges: &[(usize, usize)]) -> [[bool; N_VERTICES]; N_VERTICES] {
+    let mut graph = [[false; N_VERTICES]; N_VERTICES];
+    for &(u, v) in edges {
+        graph[u][v] = true;
+        graph[v][u] = true;
+    }
+    graph
+}
+
+fn circuit(
+    connect_strategy: impl ConnectStrategy,
+) -> (
+    CircuitData<BaseField, PGConfig, D>,
+    HamiltonianCycleTarget<N_VERTICES>,
+) {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let cycle_target =
+        HamiltonianCycleCircuitBuilder::<N_VERTICES>::add_proof_of_hamiltonian_cycle_with_connect_strategy(
+            &mut builder,
+            connect_strategy,
+        )
+        .expect("Circuit building goes wrong.");
+    (builder.build(), cycle_target)
+}
+
+#[test]
+fn test_hamiltonian_cycle() {
+    let (circuit, cycle_target) = circuit(CopyConstraint);
+
+    // A cycle visiting the vertices in a scrambled order, plus some chords.
+    let hidden_cycle = [0, 7, 3, 9, 1, 5, 8, 2, 6, 4];
+    let mut edges = Vec::from_iter(
+        (0..N_VERTICES).map(|idx| (hidden_cycle[idx], hidden_cycle[(idx + 1) % N_VERTICES])),
+    );
+    edges.extend([(0, 1), (3, 5), (2, 4), (6, 9), (7, 8)]);
+    let graph = undirected_graph(&edges);
+
+    let cycle = HamiltonianCycleWitnessBuilder::find_hamiltonian_cycle(&graph)
+        .expect("the graph has a Hamiltonian cycle");
+
+    for cycle in [hidden_cycle, cycle] {
+        let mut witness = PartialWitness::new();
+        HamiltonianCycleWitnessBuilder::set_hamiltonian_cycle_witness(
+            &mut witness,
+            &cycle_target,
+            graph,
+            cycle,
+        );
+        let proof = circuit.prove(witness).expect("proof generation goes wrong");
+        assert_eq!(
+            proof.public_inputs,
+            Vec::from_iter(
+                graph
+                    .iter()
+                    .flatten()
+                    .map(|&edge| BaseField::from_bool(edge))
+            )
+        );
+        circuit
+            .verify(proof)
+            .expect("proof verification goes wrong");
+    }
+}
+
+#[test]
+fn test_no_hamiltonian_cycle() {
+    // With `TestEq`, a witness that breaks the constraints still yields
+    // a proof, which the verifier must reject.
+    let (circuit, cycle_target) = circuit(StandardTestEq::default());
+
+    // The Petersen graph is the smallest 3-regular graph without Hamiltonian cycles.
+    let graph = undirected_graph(&Vec::from_iter(
+        (0..5).flat_map(|i| [(i, (i + 1) % 5), (5 + i, 5 + (i + 2) % 5), (i, 5 + i)]),
+    ));
+    assert!(HamiltonianCycleWitnessBuilder::find_hamiltonian_cycle(&graph).is_none());
+
+    // The outer cycle, then the inner one: the edges (4, 5) and (9, 0) are missing.
+    let mut witness = PartialWitness::new();
+    HamiltonianCycleWitnessBuilder::set_hamiltonian_cycle_witness(
+        &mut witness,
+        &cycle_target,
+        graph,
+        [0, 1, 2, 3, 4, 5, 7, 9, 6, 8],
+    );
+    // The lookups output `0` where the circuit expects `1`.
+    let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+    circuit
+        .verify(invalid_proof)
+        .expect_err("An invalid proof was accepted");
+}
@@ -1,6 +1,18 @@
+// Throughout the crate, circuit building functions report failures
+// with `Result<_, ()>`, since there is no extra information to carry.
+#![allow(clippy::result_unit_err)]
+
 pub mod permutation;
 pub mod sudoku;
 
 pub mod factorial;
 
+pub mod graph_coloring;
+
+pub mod graph_isomorphism;
+
+pub mod hamiltonian_cycle;
+
 pub mod utilities;
+
+pub mod verifier_bundle;
@@ -0,0 +1,313 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context};
+use plonky2::{
+    field::types::{Field, Field64, PrimeField64},
+    plonk::{
+        circuit_data::{VerifierCircuitData, VerifierOnlyCircuitData},
+        proof::ProofWithPublicInputs,
+    },
+};
+use plonky2_playground::{
+    factorial::{recursive_product_circuit, BaseField, PGConfig, D},
+    time_it,
+    utilities::metrics::{global_metrics, Phase},
+    verifier_bundle::{verify_with_bundle, VerifierBundle},
+};
+
+const USAGE: &str = "\
+Usage:
+    plonky2_playground product prove --n-factors N --first-factor K [OUTPUT OPTIONS]
+    plonky2_playground product verify PROOF_FILE [--verifier-data VERIFIER_DATA_FILE] [--metrics-out PATH]
+    plonky2_playground product verify PROOF_FILE --verifier-bundle VERIFIER_BUNDLE_FILE [--metrics-out PATH]
+    plonky2_playground factorial prove K [OUTPUT OPTIONS]
+
+Output options:
+    --proof-out PATH          where to write the proof (default: proof.bin)
+    --verifier-data-out PATH  where to write the verifier-only data (default: verifier_data.bin)
+    --verifier-bundle-out PATH
+                              where to write the verifier bundle (default: verifier_bundle.bin)
+    --metrics-out PATH        where to write the timings and the proof size, as CSV if PATH
+                              ends with `.csv`, as JSON otherwise (default: not written)
+
+When `--verifier-bundle` is given, `product verify` does not build the circuit.
+";
+
+const DEFAULT_PROOF_PATH: &str = "proof.bin";
+const DEFAULT_VERIFIER_DATA_PATH: &str = "verifier_data.bin";
+const DEFAULT_VERIFIER_BUNDLE_PATH: &str = "verifier_bundle.bin";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    global_metrics().enable();
+    let result = match args.as_slice() {
+        ["product", "prove", options @ ..] => product_prove(options),
+        ["product", "verify", proof_path, options @ ..] => product_verify(proof_path, options),
+        ["factorial", "prove", k, options @ ..] => factorial_prove(k, options),
+        _ => {
+            eprint!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {err:?}");
+        std::process::exit(1);
+    }
+
+    for metric in global_metrics().metrics() {
+        println!("{metric}");
+    }
+}
+
+/// The command line options, as a list of `--name value` pairs.
+struct Options<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> Options<'a> {
+    /// Parses `options` as a list of `--name value` pairs, and fails if
+    /// some option name is not contained in `allowed`.
+    fn parse(options: &[&'a str], allowed: &[&str]) -> anyhow::Result<Self> {
+        if !options.len().is_multiple_of(2) {
+            bail!("every option must be followed by a value\n\n{USAGE}");
+        }
+        let options = Vec::from_iter(options.chunks(2).map(|pair| (pair[0], pair[1])));
+        if let Some((name, _)) = options.iter().find(|(name, _)| !allowed.contains(name)) {
+            bail!("unexpected option `{name}`\n\n{USAGE}");
+        }
+        Ok(Self(options))
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn get_usize(&self, name: &str) -> anyhow::Result<usize> {
+        let value = self
+            .get(name)
+            .ok_or_else(|| anyhow!("missing option `{name}`\n\n{USAGE}"))?;
+        parse_usize(value).with_context(|| format!("invalid value for `{name}`"))
+    }
+
+    fn output_paths(&self) -> OutputPaths {
+        OutputPaths {
+            proof: self.get("--proof-out").unwrap_or(DEFAULT_PROOF_PATH).into(),
+            verifier_data: self
+                .get("--verifier-data-out")
+                .unwrap_or(DEFAULT_VERIFIER_DATA_PATH)
+                .into(),
+            verifier_bundle: self
+                .get("--verifier-bundle-out")
+                .unwrap_or(DEFAULT_VERIFIER_BUNDLE_PATH)
+                .into(),
+        }
+    }
+
+    /// Writes the metrics collected so far to the path given by
+    /// `--metrics-out`, if any.
+    fn write_metrics(&self) -> anyhow::Result<()> {
+        if let Some(metrics_path) = self.get("--metrics-out") {
+            global_metrics().write_to_file(metrics_path)?;
+            println!("Metrics written to {metrics_path}");
+        }
+        Ok(())
+    }
+}
+
+const OUTPUT_OPTIONS: [&str; 4] = [
+    "--proof-out",
+    "--verifier-data-out",
+    "--verifier-bundle-out",
+    "--metrics-out",
+];
+
+/// The files written by the `prove` commands.
+struct OutputPaths {
+    proof: PathBuf,
+    verifier_data: PathBuf,
+    verifier_bundle: PathBuf,
+}
+
+fn parse_usize(value: &str) -> anyhow::Result<usize> {
+    value
+        .parse()
+        .with_context(|| format!("`{value}` is not a non negative integer"))
+}
+
+fn product_prove(options: &[&str]) -> anyhow::Result<()> {
+    let mut allowed = vec!["--n-factors", "--first-factor"];
+    allowed.extend(OUTPUT_OPTIONS);
+    let options = Options::parse(options, &allowed)?;
+
+    let n_factors = options.get_usize("--n-factors")?;
+    let first_factor = options.get_usize("--first-factor")?;
+    prove_and_save(n_factors, first_factor, &options.output_paths())?;
+    options.write_metrics()
+}
+
+fn factorial_prove(k: &str, options: &[&str]) -> anyhow::Result<()> {
+    let options = Options::parse(options, &OUTPUT_OPTIONS)?;
+
+    // `k! = 1 * 2 * ... * k`, i.e. the product of `k` consecutive
+    // numbers, starting from `1`.
+    let n_factors = parse_usize(k).context("invalid value for `K`")?;
+    prove_and_save(n_factors, 1, &options.output_paths())?;
+    options.write_metrics()
+}
+
+fn prove_and_save(
+    n_factors: usize,
+    first_factor: usize,
+    output_paths: &OutputPaths,
+) -> anyhow::Result<()> {
+    if n_factors == 0 {
+        bail!("the number of factors must be positive");
+    }
+    let first_factor = u64::try_from(first_factor)
+        .ok()
+        .filter(|&first_factor| first_factor < BaseField::ORDER)
+        .with_context(|| {
+            format!(
+                "the first factor must be smaller than the field order {}",
+                BaseField::ORDER
+            )
+        })?;
+    // Building the circuit and proving report their metrics by themselves.
+    let circuit = recursive_product_circuit();
+
+    let proof = circuit.prove(
+        n_factors,
+        BaseField::from_canonical_u64(first_factor),
+        BaseField::ONE,
+    );
+
+    print_public_inputs(&proof)?;
+
+    time_it! {
+        circuit.verify(proof.clone())?;
+        Phase::Verification, "product/wrapper"
+    };
+
+    let proof_path = &output_paths.proof;
+    let proof_bytes = proof.to_bytes();
+    std::fs::write(proof_path, proof_bytes)
+        .with_context(|| format!("cannot write the proof to {}", proof_path.display()))?;
+    println!("Proof written to {}", proof_path.display());
+
+    let verifier_data_path = &output_paths.verifier_data;
+
+    let verifier_data_bytes = circuit
+        .verifier_only_data()
+        .to_bytes()
+        .map_err(|err| anyhow!("cannot serialize the verifier data: {err:?}"))?;
+    std::fs::write(verifier_data_path, verifier_data_bytes).with_context(|| {
+        format!(
+            "cannot write the verifier data to {}",
+            verifier_data_path.display()
+        )
+    })?;
+    println!("Verifier data written to {}", verifier_data_path.display());
+
+    let verifier_bundle_path = &output_paths.verifier_bundle;
+    circuit
+        .verifier_bundle()
+        .write_to_file(verifier_bundle_path)?;
+    println!(
+        "Verifier bundle written to {}",
+        verifier_bundle_path.display()
+    );
+
+    Ok(())
+}
+
+fn product_verify(proof_path: &str, options: &[&str]) -> anyhow::Result<()> {
+    let options = Options::parse(
+        options,
+        &["--verifier-data", "--verifier-bundle", "--metrics-out"],
+    )?;
+
+    if let Some(verifier_bundle_path) = options.get("--verifier-bundle") {
+        if options.get("--verifier-data").is_some() {
+            bail!("`--verifier-data` and `--verifier-bundle` cannot be used together");
+        }
+        product_verify_with_bundle(proof_path, verifier_bundle_path)?;
+        return options.write_metrics();
+    }
+
+    let circuit = recursive_product_circuit();
+
+    let proof_bytes =
+        std::fs::read(proof_path).with_context(|| format!("cannot read {proof_path}"))?;
+    let proof = circuit
+        .proof_from_bytes(proof_bytes)
+        .with_context(|| format!("{proof_path} does not contain a valid proof"))?;
+
+    print_public_inputs(&proof)?;
+
+    match options.get("--verifier-data") {
+        // If the verifier data is provided, we check the proof against it,
+        // instead of the verifier data of the circuit we just built.
+        Some(verifier_data_path) => {
+            let verifier_data_bytes = std::fs::read(verifier_data_path)
+                .with_context(|| format!("cannot read {verifier_data_path}"))?;
+            let verifier_data = VerifierCircuitData::<BaseField, PGConfig, D> {
+                verifier_only: VerifierOnlyCircuitData::from_bytes(verifier_data_bytes).map_err(
+                    |err| anyhow!("{verifier_data_path} does not contain verifier data: {err:?}"),
+                )?,
+                common: circuit.common_data().clone(),
+            };
+            time_it! {
+                verifier_data.verify(proof)?;
+                Phase::Verification, "product/wrapper"
+            }
+        }
+        None => time_it! {
+            circuit.verify(proof)?;
+            Phase::Verification, "product/wrapper"
+        },
+    }
+
+    println!("The proof is valid");
+    options.write_metrics()
+}
+
+fn product_verify_with_bundle(proof_path: &str, verifier_bundle_path: &str) -> anyhow::Result<()> {
+    let bundle = VerifierBundle::<BaseField, PGConfig, D>::read_from_file(verifier_bundle_path)?;
+
+    let proof_bytes =
+        std::fs::read(proof_path).with_context(|| format!("cannot read {proof_path}"))?;
+    let proof = bundle
+        .proof_from_bytes(proof_bytes)
+        .with_context(|| format!("{proof_path} does not contain a valid proof"))?;
+
+    print_public_inputs(&proof)?;
+
+    time_it! {
+        verify_with_bundle(&bundle, proof)?;
+        Phase::Verification, "product/wrapper"
+    };
+
+    println!("The proof is valid");
+    Ok(())
+}
+
+fn print_public_inputs(
+    proof: &ProofWithPublicInputs<BaseField, PGConfig, D>,
+) -> anyhow::Result<()> {
+    // The public inputs of the wrapper proof are,
+    // in order, the number of factors and their product.
+    let [n_factors, product] = proof.public_inputs[..] else {
+        bail!(
+            "the proof has {} public inputs instead of 2",
+            proof.public_inputs.len()
+        );
+    };
+    println!("Number of factors: {}", n_factors.to_canonical_u64());
+    println!("Product: {}", product.to_canonical_u64());
+    Ok(())
+}
@@ -1,15 +1,19 @@
 use super::{PermutationGate, PermutationGateWitnessGenerator};
 
 use plonky2::{
-    field::{extension::Extendable, types::Field},
-    gates::gate::Gate,
+    field::{extension::Extendable, packed::PackedField, types::Field},
+    gates::{gate::Gate, packed_util::PackedEvaluableBase, util::StridedConstraintConsumer},
     hash::hash_types::RichField,
     iop::{
         ext_target::ExtensionTarget,
         generator::{SimpleGenerator, WitnessGeneratorRef},
     },
-    plonk::{circuit_builder::CircuitBuilder, circuit_data::CommonCircuitData},
-    util::serialization::{Buffer, IoResult, Read, Write},
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::CommonCircuitData,
+        vars::{EvaluationVarsBase, EvaluationVarsBaseBatch, EvaluationVarsBasePacked},
+    },
+    util::serialization::{Buffer, IoError, IoResult, Read, Remaining, Write},
 };
 
 impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PermutationGate {
@@ -36,20 +40,44 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PermutationGat
 
     // We deserialize self as if we were serializing any other struct, the
     // only difference being that we use plonky2 methods to do it.
-    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
+    //
+    // The bytes may come from an untrusted source, so before allocating
+    // anything we check that the decoded lengths fit in the buffer, and
+    // that the gate fits in a row of the circuit described by `common_data`.
+    fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
     where
         Self: Sized,
     {
         let n_objects = src.read_usize()?;
         let swap_schedule_len = src.read_usize()?;
+
+        // Every swap is encoded as two `usize` values, followed at the end
+        // by the byte of `enforce_boolean_selectors`.
+        let encoded_len = swap_schedule_len
+            .checked_mul(2 * core::mem::size_of::<u64>())
+            .and_then(|len| len.checked_add(1))
+            .ok_or(IoError)?;
+        if src.remaining() < encoded_len {
+            return Err(IoError);
+        }
+        if !Self::max_swaps_per_row(n_objects, &common_data.config)
+            .is_some_and(|max_swaps| swap_schedule_len <= max_swaps)
+        {
+            return Err(IoError);
+        }
+
         let mut swap_schedule = Vec::with_capacity(swap_schedule_len);
         for _ in 0..swap_schedule_len {
             swap_schedule.push((src.read_usize()?, src.read_usize()?));
         }
         let enforce_boolean_selectors = src.read_bool()?;
 
-        Self::try_new(n_objects, swap_schedule, enforce_boolean_selectors)
-            .map_err(|_| plonky2::util::serialization::IoError)
+        let gate = Self::try_new(n_objects, swap_schedule, enforce_boolean_selectors)
+            .map_err(|_| IoError)?;
+        if Gate::<F, D>::num_constraints(&gate) > common_data.num_gate_constraints {
+            return Err(IoError);
+        }
+        Ok(gate)
     }
 
     // This gate will impose a number of coinstraints among targets, and this
@@ -168,6 +196,23 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PermutationGat
         constraints
     }
 
+    // The prover evaluates the constraints on the base field for every row
+    // of the LDE when computing the quotient polynomial. The default
+    // implementation of this function lifts the values to the extension field
+    // and calls `eval_unfiltered` one point at a time, so we use the
+    // vectorized evaluation of `PackedEvaluableBase` instead.
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
     // AFAIK, this tells the circuit builder what constraints have to be proved.
     // I was not able to prove it exactly, but I am pretty sure this is the
     // function we use to tell what a gate means in terms of plonk operations.
@@ -186,9 +231,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PermutationGat
         for (swap_nr, (idx1, idx2)) in self.swap_schedule_enum() {
             let selector = vars.local_wires[self.selector_wire(swap_nr)];
             if self.enforce_boolean_selectors {
-                let one = builder.one_extension();
-                let not_selector = builder.sub_extension(one, selector);
-                constraints.push(builder.mul_extension(selector, not_selector));
+                // `selector * (selector - 1)`, exactly as in `eval_unfiltered`.
+                // The sign matters: the recursive verifier compares the
+                // constraints against the quotient polynomial of the proof.
+                constraints.push(builder.mul_sub_extension(selector, selector, selector));
             }
 
             let new_idx1_wire = self.idx1_wire(swap_nr);
@@ -240,3 +286,42 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PermutationGat
         vec![WitnessGeneratorRef::new(swap_generator)]
     }
 }
+
+// Same constraints as `eval_unfiltered`, in the same order, but evaluated on
+// several points of the base field at once.
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D> for PermutationGate {
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        let mut items_tracker = Vec::from_iter((0..self.n_objects).map(|i| self.input_wire(i)));
+
+        for (swap_nr, (idx1, idx2)) in self.swap_schedule_enum() {
+            let selector = vars.local_wires[self.selector_wire(swap_nr)];
+            if self.enforce_boolean_selectors {
+                yield_constr.one(selector * (selector - P::ONES));
+            }
+
+            let new_idx1_wire = self.idx1_wire(swap_nr);
+            let new_idx2_wire = self.idx2_wire(swap_nr);
+            let item1 = vars.local_wires[items_tracker[idx1]];
+            let item2 = vars.local_wires[items_tracker[idx2]];
+
+            yield_constr.one(
+                vars.local_wires[new_idx1_wire] - (P::ONES - selector) * item1 - selector * item2,
+            );
+            yield_constr.one(
+                vars.local_wires[new_idx2_wire] - selector * item1 - (P::ONES - selector) * item2,
+            );
+
+            items_tracker[idx1] = new_idx1_wire;
+            items_tracker[idx2] = new_idx2_wire;
+        }
+
+        for (idx, item_position) in items_tracker.into_iter().enumerate() {
+            yield_constr
+                .one(vars.local_wires[self.output_wire(idx)] - vars.local_wires[item_position]);
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use std::sync::Arc;
 
 use super::{DefaultSwapSchedule, SwapIndexOutOfRange};
+use crate::utilities::connect_strategy::{ConnectStrategy, CopyConstraint};
 
 mod witness_generator;
 use plonky2::{
-    field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
-    plonk::circuit_builder::CircuitBuilder,
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::target::{BoolTarget, Target},
+    plonk::{circuit_builder::CircuitBuilder, circuit_data::CircuitConfig},
 };
 use witness_generator::PermutationGateWitnessGenerator;
 
@@ -46,7 +49,7 @@ impl PermutationGate {
             output_wires.push(Self::compute_input_wire(i));
         }
 
-        for (swap_nr, (idx1, idx2)) in swap_schedule.iter().map(|e| *e).enumerate() {
+        for (swap_nr, (idx1, idx2)) in swap_schedule.iter().copied().enumerate() {
             if n_objects <= idx1 || n_objects <= idx2 {
                 return Err(SwapIndexOutOfRange {
                     max_allowed: n_objects,
@@ -113,9 +116,24 @@ impl PermutationGate {
         self.n_objects + i
     }
 
+    /// The maximum number of swaps a gate acting on `n_objects` items can
+    /// perform, while fitting in a single row of a circuit built with `config`.
+    ///
+    /// Inputs, outputs and selectors must be connected to other targets,
+    /// so they need routed wires, while the intermediate values do not.
+    ///
+    /// Outputs `None` if not even the inputs and outputs fit in a row.
+    /// `n_objects` may come from an untrusted source, see `deserialize`.
+    pub fn max_swaps_per_row(n_objects: usize, config: &CircuitConfig) -> Option<usize> {
+        let n_io_wires = n_objects.checked_mul(2)?;
+        let routed_wires_left = config.num_routed_wires.checked_sub(n_io_wires)?;
+        let wires_left = config.num_wires.checked_sub(n_io_wires)?;
+        Some(routed_wires_left.min(wires_left / 3))
+    }
+
     /// Helper function to get an iterator over the swap schedule data.
     fn swap_schedule_enum<'a>(&'a self) -> impl Iterator<Item = (usize, (usize, usize))> + 'a {
-        self.swap_schedule.iter().map(|e| *e).enumerate()
+        self.swap_schedule.iter().copied().enumerate()
     }
 }
 
@@ -138,35 +156,75 @@ impl PermutationGate {
 
 mod out_of_the_box_general_permutation_gates {
     use super::{super::SwapSchedule, PermutationGate};
-    use std::{collections::BTreeMap, sync::Mutex};
+    use std::{any::TypeId, collections::BTreeMap, sync::Mutex};
 
-    static CACHED_GATES: Mutex<BTreeMap<(usize, bool), PermutationGate>> =
+    // Gates are indexed by `(schedule, n_objects, enforce_boolean_selectors, max_swaps_per_stage)`,
+    // where `schedule` is the type of the `SwapSchedule`, since different
+    // schedules yield different gates for the same number of items.
+    #[allow(clippy::type_complexity)]
+    static CACHED_GATES: Mutex<BTreeMap<(TypeId, usize, bool, usize), Vec<PermutationGate>>> =
         Mutex::new(BTreeMap::new());
 
-    pub fn general_permutation_gate<S: SwapSchedule>(
+    /// A single gate that performs the whole swap schedule for `n_objects` items.
+    ///
+    /// Beware that, for more than a handful of items, the gate may need more
+    /// wires than a circuit row can provide. See `general_permutation_gate_stages`.
+    pub fn general_permutation_gate<S: SwapSchedule + 'static>(
         n_objects: usize,
         enforce_boolean_selectors: bool,
     ) -> PermutationGate {
+        general_permutation_gate_stages::<S>(n_objects, enforce_boolean_selectors, usize::MAX)
+            .pop()
+            .expect("an unbounded stage contains the whole schedule")
+    }
+
+    /// Splits the swap schedule for `n_objects` items in consecutive chunks
+    /// of at most `max_swaps_per_stage` swaps, and outputs a gate for each chunk.
+    ///
+    /// Applying the gates one after the other, feeding the outputs of a gate
+    /// as inputs to the next one, amounts to applying the whole schedule.
+    pub fn general_permutation_gate_stages<S: SwapSchedule + 'static>(
+        n_objects: usize,
+        enforce_boolean_selectors: bool,
+        max_swaps_per_stage: usize,
+    ) -> Vec<PermutationGate> {
+        assert!(max_swaps_per_stage > 0);
+
+        let key = (
+            TypeId::of::<S>(),
+            n_objects,
+            enforce_boolean_selectors,
+            max_swaps_per_stage,
+        );
         let mut cache_lock = CACHED_GATES.lock().expect("Mutex is poisoned, aborting.");
-        // If we already computed the gate with the same number of inputs
+        // If we already computed the gates with the same number of inputs
         // (which is very likely), it returns the cached result.
-        if let Some(gate) = cache_lock.get(&(n_objects, enforce_boolean_selectors)) {
-            return gate.clone();
+        if let Some(gates) = cache_lock.get(&key) {
+            return gates.clone();
         }
 
         // Otherwise, it computes the result, adds it to the cache, and returns it.
-        let gate = PermutationGate::try_new(
-            n_objects,
-            S::get_swap_sequence(n_objects),
-            enforce_boolean_selectors,
-        )
-        .expect("the bubblesort schedule does not contain values greater or equal to n_objects");
-        cache_lock.insert((n_objects, enforce_boolean_selectors), gate.clone());
-
-        gate
+        let swap_sequence = S::get_swap_sequence(n_objects);
+        let gates = if swap_sequence.is_empty() {
+            // We still need a gate to copy the inputs to the outputs.
+            vec![
+                PermutationGate::try_new(n_objects, vec![], enforce_boolean_selectors)
+                    .expect("an empty schedule is always valid"),
+            ]
+        } else {
+            Vec::from_iter(swap_sequence.chunks(max_swaps_per_stage).map(|chunk| {
+                PermutationGate::try_new(n_objects, chunk.to_vec(), enforce_boolean_selectors)
+                    .expect("the schedule does not contain values greater or equal to n_objects")
+            }))
+        };
+        cache_lock.insert(key, gates.clone());
+
+        gates
     }
 }
-use out_of_the_box_general_permutation_gates::general_permutation_gate;
+use out_of_the_box_general_permutation_gates::{
+    general_permutation_gate, general_permutation_gate_stages,
+};
 
 pub trait ApplyPermutation {
     /// enforces the `inputs` targets to be a permutation of the `outputs` targets.
@@ -182,54 +240,177 @@ pub trait ApplyPermutation {
         swap_selectors: &[Target],
         outputs: &[Target],
         enforce_boolean_selectors: bool,
+    ) -> Result<(), ()> {
+        self.add_permutation_gate_with_connect_strategy(
+            inputs,
+            swap_selectors,
+            outputs,
+            enforce_boolean_selectors,
+            CopyConstraint,
+        )
+    }
+
+    /// Like `add_permutation_gate`, but the permuted items are enforced
+    /// to be equal to `outputs` through `connect_strategy`.
+    fn add_permutation_gate_with_connect_strategy(
+        &mut self,
+        inputs: &[Target],
+        swap_selectors: &[Target],
+        outputs: &[Target],
+        enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
     ) -> Result<(), ()>;
 
     /// Get the number of swap selectors needed by the gate used in [ApplyPermutation::apply_permutation]
     fn permutation_swap_schedule_length(&self, n_objects: usize) -> usize;
+
+    /// Applies the permutation determined by `swap_selectors` to the
+    /// constants `0..n_objects`. The `i`-th output is the index of the
+    /// input item that ends up in the `i`-th position.
+    ///
+    /// The outputs describe the inverse of the permutation applied by
+    /// `add_permutation_gate` with the same selectors, which has the same
+    /// parity and cycle structure.
+    fn permutation_of_indices(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+    ) -> Result<Vec<Target>, ()>;
+
+    /// Outputs the parity of the permutation determined by `swap_selectors`,
+    /// i.e. `true` if and only if the permutation is odd.
+    ///
+    /// Every selector set to `1` applies a transposition, so the parity is
+    /// the number of such selectors modulo 2. The selectors must be boolean,
+    /// as in `add_permutation_gate`.
+    fn permutation_parity(&mut self, swap_selectors: &[Target]) -> BoolTarget;
+
+    /// Enforces the permutation determined by `swap_selectors` to be a
+    /// derangement, i.e. to move every item away from its position.
+    fn assert_derangement(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+    ) -> Result<(), ()> {
+        self.assert_derangement_with_connect_strategy(
+            n_objects,
+            swap_selectors,
+            enforce_boolean_selectors,
+            CopyConstraint,
+        )
+    }
+
+    /// Like `assert_derangement`, but the absence of fixed points is
+    /// enforced through `connect_strategy`.
+    fn assert_derangement_with_connect_strategy(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<(), ()>;
+
+    /// Enforces the permutation determined by `swap_selectors` to be a
+    /// single cycle of length `n_objects`.
+    ///
+    /// We follow the orbit of the first item through the permutation: it
+    /// is a single cycle if and only if the item does not come back to its
+    /// position in less than `n_objects` steps.
+    fn assert_single_cycle(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+    ) -> Result<(), ()> {
+        self.assert_single_cycle_with_connect_strategy(
+            n_objects,
+            swap_selectors,
+            enforce_boolean_selectors,
+            CopyConstraint,
+        )
+    }
+
+    /// Like `assert_single_cycle`, but the orbit of the first item is
+    /// enforced not to go back to its position through `connect_strategy`.
+    fn assert_single_cycle_with_connect_strategy(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<(), ()>;
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> ApplyPermutation for CircuitBuilder<F, D> {
-    fn add_permutation_gate(
+    fn add_permutation_gate_with_connect_strategy(
         &mut self,
         inputs: &[Target],
         swap_selectors: &[Target],
         outputs: &[Target],
         enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
     ) -> Result<(), ()> {
         // We need the same number of input and output items.
         if inputs.len() != outputs.len() {
             return Err(());
         }
+        let n_objects = inputs.len();
 
-        let gate = general_permutation_gate::<DefaultSwapSchedule>(
-            inputs.len(),
-            enforce_boolean_selectors,
-        );
         // We need exactly one selector for every swap.
-        if swap_selectors.len() != gate.swap_schedule.len() {
+        if swap_selectors.len() != self.permutation_swap_schedule_length(n_objects) {
             return Err(());
         }
 
-        let (gate_row, op) = self.find_slot(gate.clone(), &[], &[]);
+        // If the schedule does not fit in a single row, we split it
+        // among as many gates as needed.
+        let max_swaps_per_stage = PermutationGate::max_swaps_per_row(n_objects, &self.config)
+            .filter(|&max_swaps| max_swaps > 0 || swap_selectors.is_empty())
+            .ok_or(())?;
+        let stages = general_permutation_gate_stages::<DefaultSwapSchedule>(
+            n_objects,
+            enforce_boolean_selectors,
+            max_swaps_per_stage.max(1),
+        );
 
-        // this gate does not allow more than one operation per row at the moment.
-        if op != 0 {
-            return Err(());
-        }
+        let mut stage_inputs = inputs.to_vec();
+        let mut remaining_selectors = swap_selectors;
 
-        for (idx, input) in inputs.into_iter().enumerate() {
-            self.connect(*input, Target::wire(gate_row, gate.input_wire(idx)));
-        }
+        for gate in stages {
+            let (gate_row, op) = self.find_slot(gate.clone(), &[], &[]);
+
+            // this gate does not allow more than one operation per row at the moment.
+            if op != 0 {
+                return Err(());
+            }
+
+            for (idx, input) in stage_inputs.iter().enumerate() {
+                self.connect(*input, Target::wire(gate_row, gate.input_wire(idx)));
+            }
+
+            let (stage_selectors, other_selectors) =
+                remaining_selectors.split_at(gate.swap_schedule.len());
+            for (swap_nr, selector) in stage_selectors.iter().enumerate() {
+                self.connect(
+                    *selector,
+                    Target::wire(gate_row, gate.selector_wire(swap_nr)),
+                );
+            }
+            remaining_selectors = other_selectors;
 
-        for (swap_nr, selector) in swap_selectors.into_iter().enumerate() {
-            self.connect(
-                *selector,
-                Target::wire(gate_row, gate.selector_wire(swap_nr)),
+            // The outputs of this stage are the inputs of the next one.
+            stage_inputs = Vec::from_iter(
+                (0..n_objects).map(|idx| Target::wire(gate_row, gate.output_wire(idx))),
             );
         }
 
-        for (idx, output) in outputs.into_iter().enumerate() {
-            self.connect(*output, Target::wire(gate_row, gate.output_wire(idx)));
+        // The inputs and the selectors are copied to wires that nothing else
+        // sets, so those connections hold for any witness. The outputs may
+        // have been set by the prover instead, so this is the only equality
+        // that an invalid witness can break.
+        for (output, stage_output) in outputs.iter().zip(stage_inputs) {
+            connect_strategy.connect(self, *output, stage_output);
         }
 
         Ok(())
@@ -240,4 +421,90 @@ impl<F: RichField + Extendable<D>, const D: usize> ApplyPermutation for CircuitB
             .swap_schedule()
             .len()
     }
+
+    fn permutation_of_indices(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+    ) -> Result<Vec<Target>, ()> {
+        let indices =
+            Vec::from_iter((0..n_objects).map(|idx| self.constant(F::from_canonical_usize(idx))));
+        let permuted_indices = self.add_virtual_targets(n_objects);
+        self.add_permutation_gate(
+            &indices,
+            swap_selectors,
+            &permuted_indices,
+            enforce_boolean_selectors,
+        )?;
+        Ok(permuted_indices)
+    }
+
+    fn permutation_parity(&mut self, swap_selectors: &[Target]) -> BoolTarget {
+        if swap_selectors.is_empty() {
+            return self._false();
+        }
+        // The sum of the selectors is at most `swap_selectors.len()`,
+        // so this many bits are enough to represent it.
+        let num_bits = (usize::BITS - swap_selectors.len().leading_zeros()) as usize;
+        let n_swaps = self.add_many(swap_selectors);
+        self.split_le(n_swaps, num_bits)[0]
+    }
+
+    fn assert_derangement_with_connect_strategy(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<(), ()> {
+        let permuted_indices =
+            self.permutation_of_indices(n_objects, swap_selectors, enforce_boolean_selectors)?;
+
+        // There are no fixed points if and only if the product of the
+        // differences between the indices and their positions is invertible.
+        let differences = Vec::from_iter(permuted_indices.into_iter().enumerate().map(
+            |(position, idx)| {
+                let position = self.constant(F::from_canonical_usize(position));
+                self.sub(idx, position)
+            },
+        ));
+        let product = self.mul_many(differences);
+        connect_strategy.assert_nonzero(self, product);
+
+        Ok(())
+    }
+
+    fn assert_single_cycle_with_connect_strategy(
+        &mut self,
+        n_objects: usize,
+        swap_selectors: &[Target],
+        enforce_boolean_selectors: bool,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<(), ()> {
+        let mut permuted_indices =
+            self.permutation_of_indices(n_objects, swap_selectors, enforce_boolean_selectors)?;
+        if n_objects <= 1 {
+            return Ok(());
+        }
+
+        // `random_access` needs a table whose length is a power of two.
+        // The padding is never accessed, since the indices are smaller than `n_objects`.
+        let zero = self.zero();
+        permuted_indices.resize(n_objects.next_power_of_two(), zero);
+
+        let mut orbit = Vec::with_capacity(n_objects - 1);
+        let mut position = zero;
+        for _ in 1..n_objects {
+            position = self.random_access(position, permuted_indices.clone());
+            orbit.push(position);
+        }
+
+        // None of the positions in the orbit is `0`
+        // if and only if their product is invertible.
+        let product = self.mul_many(orbit);
+        connect_strategy.assert_nonzero(self, product);
+
+        Ok(())
+    }
 }
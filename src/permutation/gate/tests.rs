@@ -1,5 +1,5 @@
 use plonky2::{
-    field::types::Field64,
+    field::types::{Field, Field64},
     fri::{reduction_strategies::FriReductionStrategy, FriConfig},
     iop::{
         target::Target,
@@ -8,8 +8,16 @@ use plonky2::{
     plonk::{circuit_builder::CircuitBuilder, circuit_data::CircuitConfig, config::GenericConfig},
 };
 
+use crate::utilities::{
+    metrics::{global_metrics, prove_with_metrics, verify_with_metrics, Phase},
+    test_connect_gate::StandardTestEq,
+};
+
 use super::{
-    super::{inverse_permutation, DefaultSwapSchedule, PermutationsIter, SwapSchedule},
+    super::{
+        inverse_permutation, BubbleSortSwapSchedule, DefaultSwapSchedule, PermutationsIter,
+        SwapSchedule,
+    },
     general_permutation_gate, ApplyPermutation,
 };
 
@@ -25,6 +33,8 @@ use super::{
 fn test_permutation_gate() {
     const N_OBJECTS: usize = 4;
 
+    global_metrics().enable();
+
     // The permutation gate gives the option to check that the selectors
     // used to swap the items around are boolean values inside, or outside
     // the gate itself. Checking outside of the gate reduces the number of
@@ -104,7 +114,7 @@ fn test_permutation_gate() {
     let items: [BaseField; N_OBJECTS] =
         core::array::from_fn(|i| BaseField::from_canonical_i64(i as i64));
 
-    // will be properly initialized and mutated by PermutationsIter
+    // a copy of the permutation yielded by PermutationsIter
     let mut permutation_buffer = [0; N_OBJECTS];
 
     // will be properly initialized and mutated to store the inverse of
@@ -113,8 +123,10 @@ fn test_permutation_gate() {
 
     // We will save the individual proofs, and aggregate them later with another circuit.
     let mut proofs = vec![];
-    for permutation in PermutationsIter::from(permutation_buffer.as_mut_slice()) {
-        let mut permutation = permutation.expect("The iterator yields a mutable reference to the slice given to the constructor. Iteration fails only if we try getting more than one such ref at the same time.");
+    let mut permutations = PermutationsIter::new(N_OBJECTS);
+    while let Some(permutation) = permutations.next_permutation() {
+        // `permutation_to_swap_schedule` scrambles its input.
+        permutation_buffer.copy_from_slice(permutation);
 
         let mut witness = PartialWitness::<BaseField>::new();
 
@@ -124,7 +136,7 @@ fn test_permutation_gate() {
         // if we apply the permutation P to the input items,
         // calling Q its inverse we will observe an output consisting of
         // (0..N_OBJECTS).map(|idx| Q(idx))
-        inverse_permutation(*permutation, inverse_p.as_mut_slice());
+        inverse_permutation(permutation, inverse_p.as_mut_slice());
         let permutated_items: [_; N_OBJECTS] = core::array::from_fn(|idx| items[inverse_p[idx]]);
         witness.set_target_arr(
             virtual_pub_inputs_permutation.as_slice(),
@@ -132,7 +144,7 @@ fn test_permutation_gate() {
         );
 
         let selectors: Vec<BaseField> =
-            DefaultSwapSchedule::permutation_to_swap_schedule(*permutation)
+            DefaultSwapSchedule::permutation_to_swap_schedule(&mut permutation_buffer)
                 .into_iter()
                 .map(|(selector, _idx1, _idx2)| BaseField::from_canonical_i64(selector.into()))
                 .collect();
@@ -140,27 +152,18 @@ fn test_permutation_gate() {
 
         let witness_clone = witness.clone();
 
-        let non_recursable_proof = crate::time_it!(
-            k_circuit.prove(witness).expect("proof generation fails");
-            "Computing the non-recursable proof takes {:?}"
-        );
-
-        println!("proof size: {}", non_recursable_proof.to_bytes().len());
+        prove_with_metrics(&k_circuit, witness, "permutation 4/keccak")
+            .expect("proof generation fails");
 
-        crate::time_it! {{
-                proofs.push(p_circuit.prove(witness_clone).expect("proof generation fails."));
-            }; "Computing the proof takes {:?}"
-        };
+        proofs.push(
+            prove_with_metrics(&p_circuit, witness_clone, "permutation 4/poseidon")
+                .expect("proof generation fails."),
+        );
 
         let proof = proofs.last().expect("we just pushed to this vector.");
-        println!("proof size: {}", proof.to_bytes().len());
 
-        crate::time_it! {
-            p_circuit
-                .verify(proof.clone())
-                .expect("proof verification fails.");
-            "Verifying the proof takes {:?}"
-        };
+        verify_with_metrics(&p_circuit, proof.clone(), "permutation 4/poseidon")
+            .expect("proof verification fails.");
     }
 
     // now we build a circuit that aggregates all the previous proofs into one.
@@ -192,27 +195,562 @@ fn test_permutation_gate() {
         witness.set_verifier_data_target(&inner_verifier_data, &p_circuit.verifier_only);
     }
 
-    let aggregated_proof_circuit = builder.build::<PGConfig>();
-
-    let aggregated_proof = crate::time_it! {
-        aggregated_proof_circuit
-            .prove(witness)
-            .expect("Generation of aggregated proof fails.");
-        "Computing the recursive proof takes {:?}"
+    let aggregated_proof_circuit = crate::time_it! {
+        builder.build::<PGConfig>();
+        Phase::CircuitBuild, "permutation 4/aggregation"
     };
 
     // We can observe that combining all the previous proofs takes a long time,
     // but the size of the combined proof is about the same as any of the
     // mon-recursive proofs!
-    println!(
-        "Size of recursive proof: {}",
-        aggregated_proof.to_bytes().len()
+    let aggregated_proof = prove_with_metrics(
+        &aggregated_proof_circuit,
+        witness,
+        "permutation 4/aggregation",
+    )
+    .expect("Generation of aggregated proof fails.");
+
+    verify_with_metrics(
+        &aggregated_proof_circuit,
+        aggregated_proof,
+        "permutation 4/aggregation",
+    )
+    .expect("Verification of aggregated proof fails.");
+
+    for metric in global_metrics().metrics() {
+        if metric.workflow().starts_with("permutation 4/") {
+            println!("{metric}");
+        }
+    }
+}
+
+/// Checks that the constraints computed by `eval_unfiltered` agree with the
+/// ones computed in-circuit by `eval_unfiltered_circuit`, which is needed to
+/// recursively verify proofs that use the permutation gate.
+#[test]
+fn test_permutation_gate_eval_fns() {
+    const D: usize = 2;
+    type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+    for n_objects in [2, 5] {
+        for enforce_boolean_selectors in [false, true] {
+            let gate = general_permutation_gate::<DefaultSwapSchedule>(
+                n_objects,
+                enforce_boolean_selectors,
+            );
+            plonky2::gates::gate_testing::test_low_degree::<BaseField, _, D>(gate.clone());
+            plonky2::gates::gate_testing::test_eval_fns::<BaseField, PGConfig, _, D>(gate)
+                .expect("eval_unfiltered and eval_unfiltered_circuit disagree");
+        }
+    }
+}
+
+/// Checks that permutations whose swap schedule does not fit in a single
+/// row are split among several gates, and are still proven correctly.
+#[test]
+fn test_permutation_gate_stages() {
+    const N_OBJECTS: usize = 16;
+    const D: usize = 2;
+    type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+    let circuit_config = CircuitConfig::standard_recursion_config();
+    let max_swaps_per_row = super::PermutationGate::max_swaps_per_row(N_OBJECTS, &circuit_config)
+        .expect("16 inputs and outputs fit in a row");
+    let n_swap_selectors = DefaultSwapSchedule::get_swap_sequence(N_OBJECTS).len();
+    assert!(n_swap_selectors > max_swaps_per_row);
+
+    let mut builder = CircuitBuilder::<BaseField, D>::new(circuit_config);
+    let inputs = builder.add_virtual_public_input_arr::<N_OBJECTS>();
+    let outputs = builder.add_virtual_public_input_arr::<N_OBJECTS>();
+    let selectors = builder.add_virtual_targets(n_swap_selectors);
+    builder
+        .add_permutation_gate(&inputs, &selectors, &outputs, true)
+        .expect("Circuit building fails while adding the permutation gate.");
+    let circuit = builder.build::<PGConfig>();
+
+    let permutation: [usize; N_OBJECTS] = core::array::from_fn(|idx| (idx * 5 + 3) % N_OBJECTS);
+    let items: [BaseField; N_OBJECTS] = core::array::from_fn(BaseField::from_canonical_usize);
+    let mut inverse_p = [0; N_OBJECTS];
+    inverse_permutation(&permutation, &mut inverse_p);
+
+    let permutated_items: [_; N_OBJECTS] = core::array::from_fn(|idx| items[inverse_p[idx]]);
+
+    let mut witness = PartialWitness::<BaseField>::new();
+    witness.set_target_arr(&inputs, &items);
+    witness.set_target_arr(&outputs, &permutated_items);
+    for (target, (selector, _idx1, _idx2)) in
+        selectors
+            .iter()
+            .zip(DefaultSwapSchedule::permutation_to_swap_schedule(
+                &mut permutation.clone(),
+            ))
+    {
+        witness.set_target(*target, BaseField::from_bool(selector));
+    }
+
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+    circuit
+        .verify(proof)
+        .expect("proof verification goes wrong");
+
+    // The gates are cached, but different schedules yield different gates
+    // for the same number of items.
+    let swaps_of_stages = |gates: Vec<super::PermutationGate>| {
+        Vec::from_iter(gates.iter().flat_map(|gate| gate.swap_schedule().to_vec()))
+    };
+    assert_eq!(
+        swaps_of_stages(
+            super::general_permutation_gate_stages::<DefaultSwapSchedule>(
+                N_OBJECTS,
+                true,
+                max_swaps_per_row
+            )
+        ),
+        DefaultSwapSchedule::get_swap_sequence(N_OBJECTS)
+    );
+    assert_eq!(
+        swaps_of_stages(super::general_permutation_gate_stages::<
+            BubbleSortSwapSchedule,
+        >(N_OBJECTS, true, max_swaps_per_row)),
+        BubbleSortSwapSchedule::get_swap_sequence(N_OBJECTS)
+    );
+}
+
+/// Checks the parity, derangement and single cycle gadgets
+/// on permutations of 5 items with different cycle structures.
+#[test]
+fn test_permutation_properties() {
+    const N_OBJECTS: usize = 5;
+    const D: usize = 2;
+    type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let selectors =
+        builder.add_virtual_targets(builder.permutation_swap_schedule_length(N_OBJECTS));
+    builder
+        .permutation_of_indices(N_OBJECTS, &selectors, true)
+        .expect("Circuit building fails while adding the permutation gate.");
+    let parity = builder.permutation_parity(&selectors);
+    builder.register_public_input(parity.target);
+    let parity_circuit = builder.build::<PGConfig>();
+
+    // With `TestEq`, the witnesses that break a property still yield
+    // a proof, which the verifier rejects.
+    type AssertFn = fn(&mut CircuitBuilder<BaseField, D>, usize, &[Target], bool) -> Result<(), ()>;
+    let [derangement_circuit, single_cycle_circuit] = [
+        (|builder, n_objects, selectors, enforce_boolean_selectors| {
+            builder.assert_derangement_with_connect_strategy(
+                n_objects,
+                selectors,
+                enforce_boolean_selectors,
+                StandardTestEq::default(),
+            )
+        }) as AssertFn,
+        |builder, n_objects, selectors, enforce_boolean_selectors| {
+            builder.assert_single_cycle_with_connect_strategy(
+                n_objects,
+                selectors,
+                enforce_boolean_selectors,
+                StandardTestEq::default(),
+            )
+        },
+    ]
+    .map(|assert_property| {
+        let mut builder =
+            CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+        let selectors =
+            builder.add_virtual_targets(builder.permutation_swap_schedule_length(N_OBJECTS));
+        assert_property(&mut builder, N_OBJECTS, &selectors, true)
+            .expect("Circuit building fails while adding the permutation gate.");
+        (builder.build::<PGConfig>(), selectors)
+    });
+
+    let selector_values = |permutation: [usize; N_OBJECTS]| {
+        Vec::from_iter(
+            DefaultSwapSchedule::permutation_to_swap_schedule(&mut permutation.clone())
+                .into_iter()
+                .map(|(selector, _idx1, _idx2)| BaseField::from_bool(selector)),
+        )
+    };
+
+    // (permutation, is odd, is a derangement, is a single cycle)
+    let test_cases = [
+        ([0, 1, 2, 3, 4], false, false, false),
+        ([1, 0, 2, 3, 4], true, false, false),
+        ([1, 2, 0, 3, 4], false, false, false),
+        ([1, 0, 3, 4, 2], true, true, false),
+        ([1, 2, 3, 4, 0], false, true, true),
+        ([2, 3, 4, 0, 1], false, true, true),
+        ([4, 3, 1, 0, 2], false, true, true),
+        ([3, 4, 1, 0, 2], true, true, false),
+    ];
+
+    for (permutation, is_odd, is_derangement, is_single_cycle) in test_cases {
+        let values = selector_values(permutation);
+
+        let mut witness = PartialWitness::new();
+        witness.set_target_arr(&selectors, &values);
+        let proof = parity_circuit
+            .prove(witness)
+            .expect("proof generation goes wrong");
+        assert_eq!(proof.public_inputs, vec![BaseField::from_bool(is_odd)]);
+        parity_circuit
+            .verify(proof)
+            .expect("proof verification goes wrong");
+
+        for ((circuit, selectors), expected) in [
+            (&derangement_circuit, is_derangement),
+            (&single_cycle_circuit, is_single_cycle),
+        ] {
+            let mut witness = PartialWitness::new();
+            witness.set_target_arr(selectors, &values);
+            let proof = circuit.prove(witness).expect("proof generation goes wrong");
+            assert_eq!(
+                circuit.verify(proof).is_ok(),
+                expected,
+                "wrong outcome for {permutation:?}"
+            );
+        }
+    }
+}
+
+/// Feeds random and corrupted buffers to the deserializers of the gate and
+/// of its witness generator. They must never panic, nor allocate according
+/// to lengths that do not fit in the buffer, and every gate they output
+/// must fit in a row of the circuit.
+#[test]
+fn test_permutation_gate_deserialization_fuzz() {
+    use plonky2::{
+        gates::gate::Gate,
+        iop::generator::SimpleGenerator,
+        util::serialization::{Buffer, Write},
+    };
+    use rand::Rng;
+
+    const N_OBJECTS: usize = 9;
+    const N_RANDOM_BUFFERS: usize = 10_000;
+    const D: usize = 2;
+    type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+    let circuit_config = CircuitConfig::standard_recursion_config();
+    let n_swap_selectors = DefaultSwapSchedule::get_swap_sequence(N_OBJECTS).len();
+    let mut builder = CircuitBuilder::<BaseField, D>::new(circuit_config.clone());
+    let inputs = builder.add_virtual_target_arr::<N_OBJECTS>();
+    let outputs = builder.add_virtual_target_arr::<N_OBJECTS>();
+    let selectors = builder.add_virtual_targets(n_swap_selectors);
+    builder
+        .add_permutation_gate(&inputs, &selectors, &outputs, true)
+        .expect("Circuit building fails while adding the permutation gate.");
+    let common_data = builder.build::<PGConfig>().common;
+
+    let gate = super::general_permutation_gate_stages::<DefaultSwapSchedule>(
+        N_OBJECTS,
+        true,
+        super::PermutationGate::max_swaps_per_row(N_OBJECTS, &circuit_config)
+            .expect("9 inputs and outputs fit in a row"),
+    )
+    .swap_remove(0);
+
+    let deserialize_gate = |bytes: &[u8]| {
+        <super::PermutationGate as Gate<BaseField, D>>::deserialize(
+            &mut Buffer::new(bytes),
+            &common_data,
+        )
+    };
+    let deserialize_generator = |bytes: &[u8]| {
+        <super::PermutationGateWitnessGenerator as SimpleGenerator<BaseField, D>>::deserialize(
+            &mut Buffer::new(bytes),
+            &common_data,
+        )
+    };
+    let check_gate = |gate: &super::PermutationGate| {
+        assert!(Gate::<BaseField, D>::num_wires(gate) <= circuit_config.num_wires);
+        assert!(Gate::<BaseField, D>::num_constraints(gate) <= common_data.num_gate_constraints);
+    };
+
+    // Valid encodings are decoded.
+    let mut gate_bytes = Vec::new();
+    Gate::<BaseField, D>::serialize(&gate, &mut gate_bytes, &common_data)
+        .expect("serialization fails");
+    let decoded_gate = deserialize_gate(&gate_bytes).expect("a valid gate is rejected");
+    assert_eq!(
+        Gate::<BaseField, D>::id(&decoded_gate),
+        Gate::<BaseField, D>::id(&gate)
     );
 
-    crate::time_it! {
-        aggregated_proof_circuit
-            .verify(aggregated_proof)
-            .expect("Verification of aggregated proof fails.");
-        "Verifying the recursive proof takes {:?}"
+    let generator_bytes = |row: usize| {
+        let mut bytes = Vec::new();
+        bytes.write_usize(row).expect("serialization fails");
+        bytes.extend_from_slice(&gate_bytes);
+        bytes
+    };
+    let last_row = common_data.degree() - 1;
+    let generator =
+        deserialize_generator(&generator_bytes(last_row)).expect("a valid generator is rejected");
+    assert_eq!(generator.row, last_row);
+
+    // Rows outside of the circuit are rejected.
+    assert!(deserialize_generator(&generator_bytes(last_row + 1)).is_err());
+    assert!(deserialize_generator(&generator_bytes(usize::MAX)).is_err());
+
+    // Lengths that do not fit in the buffer, or gates that do not fit
+    // in a row, are rejected before allocating anything.
+    for (n_objects, swap_schedule_len) in [
+        (N_OBJECTS, usize::MAX),
+        (N_OBJECTS, usize::MAX / 16),
+        (N_OBJECTS, 1 << 40),
+        (1 << 40, 0),
+        (usize::MAX, 0),
+        (circuit_config.num_routed_wires, 0),
+    ] {
+        let mut bytes = Vec::new();
+        bytes.write_usize(n_objects).expect("serialization fails");
+        bytes
+            .write_usize(swap_schedule_len)
+            .expect("serialization fails");
+        bytes.write_bool(true).expect("serialization fails");
+        assert!(deserialize_gate(&bytes).is_err());
+    }
+
+    // A schedule that does not fit in a row is rejected, even if it is
+    // fully encoded in the buffer. See `test_permutation_gate_stages`.
+    let too_long_schedule =
+        super::PermutationGate::try_new(16, DefaultSwapSchedule::get_swap_sequence(16), true)
+            .expect("the default schedule is valid");
+    let mut bytes = Vec::new();
+    Gate::<BaseField, D>::serialize(&too_long_schedule, &mut bytes, &common_data)
+        .expect("serialization fails");
+    assert!(deserialize_gate(&bytes).is_err());
+
+    let mut rng = crate::utilities::seeded_rng::test_rng();
+    for _ in 0..N_RANDOM_BUFFERS {
+        // Random buffers, whose lengths are random too.
+        let len = rng.gen_range(0..4 * gate_bytes.len());
+        let bytes = Vec::from_iter((0..len).map(|_| rng.gen::<u8>()));
+        if let Ok(gate) = deserialize_gate(&bytes) {
+            check_gate(&gate);
+        }
+        if let Ok(generator) = deserialize_generator(&bytes) {
+            assert!(generator.row < common_data.degree());
+            check_gate(&generator.gate);
+        }
+
+        // Valid encodings with a few corrupted bytes.
+        let mut bytes = gate_bytes.clone();
+        for _ in 0..rng.gen_range(1..4) {
+            let idx = rng.gen_range(0..bytes.len());
+            bytes[idx] = rng.gen();
+        }
+        if let Ok(gate) = deserialize_gate(&bytes) {
+            check_gate(&gate);
+        }
+    }
+}
+
+/// A change to the wires of a `PermutationGate` in an honest witness.
+/// See `mutate_gate_wires`.
+#[derive(Debug, Clone, Copy)]
+enum GateWireMutation {
+    /// Adds one to the value of the wire, leaving the others untouched.
+    Shift(usize),
+    /// Sets the `swap_nr`-th selector to `2`, then recomputes the
+    /// intermediate values and the outputs of the following swaps, so that
+    /// the swap constraints hold for the non-boolean selector.
+    NonBooleanSelector(usize),
+}
+
+/// The mutations of the selectors, of the intermediate values and of the
+/// outputs of `gate`. The inputs are not mutated, since they are usually
+/// public, and changing them changes the statement.
+fn gate_wire_mutations(gate: &super::PermutationGate) -> Vec<GateWireMutation> {
+    let n_swaps = gate.swap_schedule().len();
+    Vec::from_iter(
+        (0..n_swaps)
+            .flat_map(|swap_nr| {
+                [
+                    GateWireMutation::NonBooleanSelector(swap_nr),
+                    GateWireMutation::Shift(gate.selector_wire(swap_nr)),
+                    GateWireMutation::Shift(gate.idx1_wire(swap_nr)),
+                    GateWireMutation::Shift(gate.idx2_wire(swap_nr)),
+                ]
+            })
+            .chain((0..gate.n_objects()).map(|idx| GateWireMutation::Shift(gate.output_wire(idx)))),
+    )
+}
+
+/// Applies `mutation` to the `wires` of `gate`, which are laid out as in a
+/// row of the circuit.
+fn mutate_gate_wires<F: Field>(
+    gate: &super::PermutationGate,
+    wires: &mut [F],
+    mutation: GateWireMutation,
+) {
+    match mutation {
+        GateWireMutation::Shift(wire) => wires[wire] += F::ONE,
+        GateWireMutation::NonBooleanSelector(mutated_swap_nr) => {
+            wires[gate.selector_wire(mutated_swap_nr)] = F::TWO;
+
+            // The same computation as the witness generator, except that
+            // the values are interpolated instead of swapped.
+            let mut item_values =
+                Vec::from_iter((0..gate.n_objects()).map(|idx| wires[gate.input_wire(idx)]));
+            for (swap_nr, &(idx1, idx2)) in gate.swap_schedule().iter().enumerate() {
+                let selector = wires[gate.selector_wire(swap_nr)];
+                let (item1, item2) = (item_values[idx1], item_values[idx2]);
+                item_values[idx1] = item1 + selector * (item2 - item1);
+                item_values[idx2] = item2 + selector * (item1 - item2);
+                if swap_nr >= mutated_swap_nr {
+                    wires[gate.idx1_wire(swap_nr)] = item_values[idx1];
+                    wires[gate.idx2_wire(swap_nr)] = item_values[idx2];
+                }
+            }
+            for (idx, value) in item_values.into_iter().enumerate() {
+                wires[gate.output_wire(idx)] = value;
+            }
+        }
+    }
+}
+
+/// Applies every mutation of `gate_wire_mutations` to an honest witness of
+/// a permutation gate, with and without `enforce_boolean_selectors`.
+///
+/// Every mutation must make `eval_unfiltered` output a non-zero constraint,
+/// except for the non-boolean selectors of a gate that does not enforce
+/// them to be boolean: in that case the gate is satisfied, and the circuit
+/// must check the selectors elsewhere, as `add_permutation_gate` does.
+/// Either way, the proofs built from the mutated witnesses must fail
+/// verification.
+#[test]
+fn test_permutation_gate_witness_mutations() {
+    use plonky2::{
+        field::extension::FieldExtension,
+        gates::gate::Gate,
+        hash::hash_types::HashOut,
+        iop::{generator::generate_partial_witness, witness::Witness},
+        plonk::{prover::prove_with_partition_witness, vars::EvaluationVars},
+        util::timing::TimingTree,
     };
+
+    const N_OBJECTS: usize = 4;
+    const D: usize = 2;
+    type PGConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+    type ExtensionField = <BaseField as plonky2::field::extension::Extendable<D>>::Extension;
+
+    let permutation = [2, 0, 3, 1];
+    let items: [BaseField; N_OBJECTS] =
+        core::array::from_fn(|idx| BaseField::from_canonical_usize(idx + 1));
+    let selector_values = Vec::from_iter(
+        DefaultSwapSchedule::permutation_to_swap_schedule(&mut permutation.clone())
+            .into_iter()
+            .map(|(selector, _idx1, _idx2)| BaseField::from_bool(selector)),
+    );
+
+    for enforce_boolean_selectors in [false, true] {
+        let gate =
+            general_permutation_gate::<DefaultSwapSchedule>(N_OBJECTS, enforce_boolean_selectors);
+
+        // We add the gate by hand, so that we know its row.
+        let mut builder =
+            CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+        let row = builder.add_gate(gate.clone(), vec![]);
+        let inputs = builder.add_virtual_public_input_arr::<N_OBJECTS>();
+        for (idx, input) in inputs.iter().enumerate() {
+            builder.connect(*input, Target::wire(row, gate.input_wire(idx)));
+        }
+        let selectors = Vec::from_iter(
+            (0..gate.swap_schedule().len())
+                .map(|swap_nr| Target::wire(row, gate.selector_wire(swap_nr))),
+        );
+        if !enforce_boolean_selectors {
+            for selector in selectors.iter() {
+                let bool_target = builder.add_virtual_bool_target_safe();
+                builder.connect(bool_target.target, *selector);
+            }
+        }
+        let circuit = builder.build::<PGConfig>();
+
+        let mut witness = PartialWitness::new();
+        witness.set_target_arr(&inputs, &items);
+        witness.set_target_arr(&selectors, &selector_values);
+        let honest_witness =
+            generate_partial_witness(witness, &circuit.prover_only, &circuit.common);
+        let honest_wires = Vec::from_iter(
+            (0..Gate::<BaseField, D>::num_wires(&gate))
+                .map(|column| honest_witness.get_target(Target::wire(row, column))),
+        );
+
+        let evaluate_gate = |wires: &[BaseField]| {
+            let local_wires = Vec::from_iter(
+                wires
+                    .iter()
+                    .map(|&w| <ExtensionField as FieldExtension<D>>::from_basefield(w)),
+            );
+            Gate::<BaseField, D>::eval_unfiltered(
+                &gate,
+                EvaluationVars {
+                    local_constants: &[],
+                    local_wires: &local_wires,
+                    public_inputs_hash: &HashOut::ZERO,
+                },
+            )
+        };
+        assert!(evaluate_gate(&honest_wires)
+            .into_iter()
+            .all(|constraint| constraint == ExtensionField::ZERO));
+
+        let honest_proof = prove_with_partition_witness(
+            &circuit.prover_only,
+            &circuit.common,
+            honest_witness.clone(),
+            &mut TimingTree::default(),
+        )
+        .expect("proof generation goes wrong");
+        circuit
+            .verify(honest_proof)
+            .expect("proof verification goes wrong");
+
+        for mutation in gate_wire_mutations(&gate) {
+            let mut wires = honest_wires.clone();
+            mutate_gate_wires(&gate, &mut wires, mutation);
+
+            let gate_is_satisfied = evaluate_gate(&wires)
+                .into_iter()
+                .all(|constraint| constraint == ExtensionField::ZERO);
+            assert_eq!(
+                gate_is_satisfied,
+                matches!(mutation, GateWireMutation::NonBooleanSelector(_))
+                    && !enforce_boolean_selectors,
+                "enforce_boolean_selectors: {enforce_boolean_selectors}, {mutation:?}"
+            );
+
+            // We write the mutated values directly in the partition witness,
+            // since the witness generation would complain that they are
+            // not the ones computed by the gate. The values of the wires that
+            // are copied together are stored once, so copy constraints hold.
+            let mut mutated_witness = honest_witness.clone();
+            for (column, value) in wires.into_iter().enumerate() {
+                let target_index = Target::wire(row, column)
+                    .index(circuit.common.config.num_wires, circuit.common.degree());
+                mutated_witness.values[mutated_witness.representative_map[target_index]] =
+                    Some(value);
+            }
+            let proof = prove_with_partition_witness(
+                &circuit.prover_only,
+                &circuit.common,
+                mutated_witness,
+                &mut TimingTree::default(),
+            )
+            .expect("proof generation goes wrong");
+            assert_eq!(proof.public_inputs, items);
+            circuit.verify(proof).expect_err(&format!(
+                "enforce_boolean_selectors: {enforce_boolean_selectors}, {mutation:?} is accepted"
+            ));
+        }
+    }
 }
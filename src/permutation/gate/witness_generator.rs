@@ -8,7 +8,7 @@ use plonky2::{
         witness::{Witness, WitnessWrite},
     },
     plonk::circuit_data::CommonCircuitData,
-    util::serialization::{Buffer, IoResult, Read, Write},
+    util::serialization::{Buffer, IoError, IoResult, Read, Write},
 };
 
 use super::PermutationGate;
@@ -29,7 +29,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     for PermutationGateWitnessGenerator
 {
     fn id(&self) -> String {
-        format! {"self:?"}
+        format!("{self:?}")
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -41,8 +41,15 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     where
         Self: Sized,
     {
+        // The generator of a gate that lies outside of the
+        // circuit would write to wires that do not exist.
+        let row = src.read_usize()?;
+        if row >= common_data.degree() {
+            return Err(IoError);
+        }
+
         Ok(Self {
-            row: src.read_usize()?,
+            row,
             gate: PermutationGate::deserialize(src, common_data)?,
         })
     }
@@ -8,8 +8,17 @@ pub use swap_schedule::{
     BubbleSortSwapSchedule, DefaultSwapSchedule, RecusriveSplitTwoSchedule, SwapSchedule,
 };
 
+mod swap_schedule_conformance;
+pub use swap_schedule_conformance::{
+    check_swap_schedule, swap_sequence_depth, SwapScheduleReport, SwapScheduleViolation,
+    MAX_EXHAUSTIVE_OBJECTS,
+};
+
 mod permutation_utilities;
-pub use permutation_utilities::{inverse_permutation, KnuthL, PermutationsIter};
+pub use permutation_utilities::{
+    inverse_permutation, n_permutations, random_permutation, rank_permutation, unrank_permutation,
+    HeapPermutations, PermutationsIter, MAX_RANKED_OBJECTS,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct SwapIndexOutOfRange {
@@ -1,136 +1,281 @@
-use std::{
-    cell::{BorrowMutError, RefCell, RefMut},
-    marker::PhantomData,
-};
+use std::ops::Range;
 
-/// A struct that, given a buffer to write on, iterates over all the
-/// permutations of a given length.
+use rand::{seq::SliceRandom, Rng};
+
+/// The largest number of objects whose permutations can be ranked,
+/// since `20!` is the largest factorial that fits in a `u64`.
+pub const MAX_RANKED_OBJECTS: usize = 20;
+
+/// The number of permutations of `n_objects` objects, i.e. `n_objects!`,
+/// or `None` if it does not fit in a `u64`.
+pub fn n_permutations(n_objects: usize) -> Option<u64> {
+    (1..=n_objects as u64).try_fold(1u64, |factorial, n| factorial.checked_mul(n))
+}
+
+/// Iterates over the permutations of `0..n_objects` in lexicographic order,
+/// following Knuth's algorithm L.
+///
+/// It is a lending iterator: `next_permutation` yields a reference to a
+/// buffer owned by `self`, which is overwritten by the following call.
+/// To keep a permutation around, copy it, e.g. with `to_vec`.
 ///
-/// Notice that the iterator yields RefCell guards to the underlying
-/// buffer, therefore only one iteration item can be used at any given time.
+/// ``` rust
+/// use plonky2_playground::permutation::PermutationsIter;
 ///
-/// To use more items at once, their result must be copied in other
-/// memory locations, and the RefCell guards must go out of scope.
-pub struct PermutationsIter<'a: 'i, 'i> {
-    iterator_output: &'a mut [usize],
-    pd: PhantomData<&'i ()>,
+/// let mut permutations = PermutationsIter::new(3);
+/// while let Some(permutation) = permutations.next_permutation() {
+///     assert_eq!(permutation.len(), 3);
+/// }
+/// ```
+///
+/// The `k`-th permutation yielded by `new` is the one of rank `k`, see
+/// `rank_permutation`, so that a range of ranks can be iterated over
+/// with `with_rank_range`, and all the permutations can be split
+/// among several threads with `split`.
+#[derive(Debug, Clone)]
+pub struct PermutationsIter {
+    permutation: Vec<usize>,
+    started: bool,
+    done: bool,
+    /// The number of permutations left to yield, or `None` if the
+    /// iteration only stops after the last permutation.
+    remaining: Option<u64>,
 }
 
-impl<'a: 'i, 'i> From<&'a mut [usize]> for PermutationsIter<'a, 'i> {
-    fn from(value: &'a mut [usize]) -> Self {
+impl PermutationsIter {
+    /// Iterates over all the permutations of `0..n_objects`.
+    pub fn new(n_objects: usize) -> Self {
         Self {
-            iterator_output: value,
-            pd: PhantomData,
+            permutation: Vec::from_iter(0..n_objects),
+            started: false,
+            done: false,
+            remaining: None,
         }
     }
-}
 
-impl<'a: 'i, 'i> IntoIterator for PermutationsIter<'a, 'i> {
-    type IntoIter = KnuthL<'a, 'i>;
-    type Item = <KnuthL<'a, 'i> as Iterator>::Item;
-    fn into_iter(self) -> Self::IntoIter {
-        KnuthL::new(self.iterator_output)
+    /// Iterates over the permutations of `0..n_objects` whose rank is in `ranks`.
+    ///
+    /// Fails if `n_objects > MAX_RANKED_OBJECTS`, or if `ranks` does not
+    /// fit in `0..n_objects!`.
+    pub fn with_rank_range(n_objects: usize, ranks: Range<u64>) -> Result<Self, ()> {
+        let n_permutations = n_permutations(n_objects).ok_or(())?;
+        if ranks.start > ranks.end || ranks.end > n_permutations {
+            return Err(());
+        }
+        let permutation = if ranks.start < n_permutations {
+            unrank_permutation(n_objects, ranks.start)?
+        } else {
+            Vec::from_iter(0..n_objects)
+        };
+        Ok(Self {
+            permutation,
+            started: false,
+            done: false,
+            remaining: Some(ranks.end - ranks.start),
+        })
     }
-}
 
-/// A struct that iterates over all the permutations of a given length.
-pub struct KnuthL<'a: 'i, 'i> {
-    permutation: Option<Vec<usize>>,
-    iterator_output: std::cell::RefCell<&'a mut [usize]>,
-    pd: PhantomData<&'i ()>,
-}
+    /// Splits the permutations of `0..n_objects` into `n_parts` iterators
+    /// over contiguous ranges of ranks, whose lengths differ by at most one.
+    ///
+    /// Fails if `n_objects > MAX_RANKED_OBJECTS` or `n_parts == 0`.
+    pub fn split(n_objects: usize, n_parts: usize) -> Result<Vec<Self>, ()> {
+        let n_permutations = n_permutations(n_objects).ok_or(())?;
+        if n_parts == 0 {
+            return Err(());
+        }
+        let n_parts = n_parts as u64;
+        (0..n_parts)
+            .map(|part| {
+                let start = n_permutations / n_parts * part + part.min(n_permutations % n_parts);
+                let end = n_permutations / n_parts * (part + 1)
+                    + (part + 1).min(n_permutations % n_parts);
+                Self::with_rank_range(n_objects, start..end)
+            })
+            .collect()
+    }
 
-impl<'a: 'i, 'i> KnuthL<'a, 'i> {
-    fn new(iterator_output: &'a mut [usize]) -> Self {
-        let n_objects = iterator_output.len();
-        let iterator_output = RefCell::new(iterator_output);
-        if n_objects == 0 {
-            return Self {
-                permutation: None,
-                iterator_output,
-                pd: PhantomData,
-            };
-        }
-
-        let permutation = Some({
-            let mut v = Vec::with_capacity(n_objects);
-            v.extend(0..n_objects);
-            v
-        });
+    /// The next permutation, or `None` if there are no more.
+    pub fn next_permutation(&mut self) -> Option<&[usize]> {
+        if self.done || self.remaining == Some(0) {
+            self.done = true;
+            return None;
+        }
+        if self.started && !next_lexicographic_permutation(&mut self.permutation) {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        Some(&self.permutation)
+    }
 
-        Self {
-            permutation,
-            iterator_output,
-            pd: PhantomData,
+    /// Calls `f` on every remaining permutation.
+    pub fn for_each(mut self, mut f: impl FnMut(&[usize])) {
+        while let Some(permutation) = self.next_permutation() {
+            f(permutation);
         }
     }
 }
 
-impl<'a, 'i> Iterator for KnuthL<'a, 'i> {
-    type Item = Result<RefMut<'i, &'a mut [usize]>, BorrowMutError>;
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.permutation == None {
-            return None;
-        }
+/// Rearranges `permutation` into the next one in lexicographic order.
+/// Returns `false`, leaving `permutation` unchanged, if it is the last one.
+fn next_lexicographic_permutation(permutation: &mut [usize]) -> bool {
+    let n_objects = permutation.len();
+    if n_objects < 2 {
+        return false;
+    }
 
-        // Try borrowing the output buffer. If the borrow operation fails,
-        // Some(Err(...)) is returned, and the iterator's state is left unchanged.
-        let output = self.iterator_output.try_borrow_mut();
-
-        let mut output = output.map(|borrow| unsafe {
-            // About the unsafe block: the data sctucture we want to use as iteration item
-            // cannot have a generic lifetime, since it contains a `'a` lived pointer.
-            // We can safely make the compiler believe that the returned variable lives `'i`
-            // because the underlying data lives `'a`, which outlives `'i`
-            // and the compiler will deduce `'i` form the scope the output items live in.
-            //
-            // Also, this function does not actually need type annotations,
-            // but we want to avoid pesky bugs that could emerge with refactoring
-            // if the annotations weren't there.
-            core::mem::transmute::<RefMut<'_, &'a mut [usize]>, RefMut<'i, &'a mut [usize]>>(borrow)
-        });
+    // Find the last j such that permutation[j] < permutation[j + 1].
+    // If there is none, the permutation is [n_objects - 1, ..., 1, 0].
+    let Some(j) = (0..n_objects - 1)
+        .rev()
+        .find(|&j| permutation[j] < permutation[j + 1])
+    else {
+        return false;
+    };
 
-        if output.is_err() {
-            return Some(output);
+    // Find the last l such that permutation[j] < permutation[l], then
+    // exchange the items j and l, and reverse permutation[j + 1..].
+    let l = (j + 1..n_objects)
+        .rev()
+        .find(|&l| permutation[j] < permutation[l])
+        .expect("`j + 1` is in the range, and satisfies the condition by definition of `j`");
+    permutation.swap(j, l);
+    permutation[j + 1..].reverse();
+    true
+}
+
+/// Iterates over the permutations of `0..n_objects` following Heap's
+/// algorithm, in which every permutation is obtained from the previous
+/// one by a single swap, returned by `last_swap`.
+///
+/// Like `PermutationsIter`, it is a lending iterator.
+#[derive(Debug, Clone)]
+pub struct HeapPermutations {
+    permutation: Vec<usize>,
+    /// The loop counters of the non-recursive version of Heap's algorithm.
+    counters: Vec<usize>,
+    /// The position of the counter to increment next.
+    position: usize,
+    started: bool,
+    last_swap: Option<(usize, usize)>,
+}
+
+impl HeapPermutations {
+    pub fn new(n_objects: usize) -> Self {
+        Self {
+            permutation: Vec::from_iter(0..n_objects),
+            counters: vec![0; n_objects],
+            position: 1,
+            started: false,
+            last_swap: None,
         }
+    }
 
-        let array = self
-            .permutation
-            .as_mut()
-            .expect("we checked at the beginning that self.permutation != None")
-            .as_mut_slice();
+    /// The next permutation, or `None` if there are no more.
+    pub fn next_permutation(&mut self) -> Option<&[usize]> {
+        if !self.started {
+            self.started = true;
+            return Some(&self.permutation);
+        }
 
-        let n_objects = array.len();
+        while self.position < self.permutation.len() {
+            let position = self.position;
+            if self.counters[position] < position {
+                let swap = if position.is_multiple_of(2) {
+                    (0, position)
+                } else {
+                    (self.counters[position], position)
+                };
+                self.permutation.swap(swap.0, swap.1);
+                self.last_swap = Some(swap);
+                self.counters[position] += 1;
+                self.position = 1;
+                return Some(&self.permutation);
+            }
+            self.counters[position] = 0;
+            self.position += 1;
+        }
+        self.last_swap = None;
+        None
+    }
 
-        // Copy the current state, to output buffer.
-        output
-            .as_mut()
-            .expect("If output was Err, we would have returned it by now.")
-            .copy_from_slice(array);
+    /// The swap that turned the previous permutation into the last one
+    /// yielded by `next_permutation`, or `None` if there is no such swap,
+    /// i.e. at the first permutation and after the last one.
+    pub fn last_swap(&self) -> Option<(usize, usize)> {
+        self.last_swap
+    }
 
-        // Find last j such that self[j] <= self[j+1].
-        // Nullify self.0 if it doesn't exist
-        let j = (0..=n_objects - 2)
-            .rev()
-            .find(|&j| array[j] <= array[j + 1]);
+    /// Calls `f` on every remaining permutation.
+    pub fn for_each(mut self, mut f: impl FnMut(&[usize])) {
+        while let Some(permutation) = self.next_permutation() {
+            f(permutation);
+        }
+    }
+}
 
-        // The last permutation we yield is [N_OBJECTS - 1, N_OBJECTS - 2, ..., 1, 0]
-        if j == None {
-            self.permutation = None;
-            return Some(output);
+/// The rank of `permutation` among the permutations of its length sorted
+/// in lexicographic order, computed from its Lehmer code.
+///
+/// Fails if `permutation` is not a permutation of `0..permutation.len()`,
+/// or if it is longer than `MAX_RANKED_OBJECTS`.
+pub fn rank_permutation(permutation: &[usize]) -> Result<u64, ()> {
+    let n_objects = permutation.len();
+    if n_objects > MAX_RANKED_OBJECTS {
+        return Err(());
+    }
+    let mut seen = vec![false; n_objects];
+    for &item in permutation {
+        if item >= n_objects || std::mem::replace(&mut seen[item], true) {
+            return Err(());
         }
+    }
 
-        let j = j.unwrap();
+    // The i-th digit of the Lehmer code is the number of items after
+    // the i-th one that are smaller than it, and it has weight (n - 1 - i)!
+    Ok(permutation.iter().enumerate().fold(0, |rank, (i, &item)| {
+        let digit = permutation[i + 1..]
+            .iter()
+            .filter(|&&later| later < item)
+            .count();
+        rank * (n_objects - i) as u64 + digit as u64
+    }))
+}
 
-        // Find last l such that self[j] <= self[l], then
-        // exchange elements j and l, and then reverse self[j+1..]
-        let l = (j + 1..=n_objects - 1).rev().find(|&l| array[j] <= array[l])
-        .expect("since `j + 1` is in the range, and given the definition of `j`, we are sure that `find` will return `Some(...)`");
-        array.swap(j, l);
-        array[j + 1..].reverse();
+/// The permutation of `0..n_objects` of rank `rank`, i.e. the inverse
+/// of `rank_permutation`.
+///
+/// Fails if `n_objects > MAX_RANKED_OBJECTS` or `rank >= n_objects!`.
+pub fn unrank_permutation(n_objects: usize, rank: u64) -> Result<Vec<usize>, ()> {
+    if n_objects > MAX_RANKED_OBJECTS || rank >= n_permutations(n_objects).ok_or(())? {
+        return Err(());
+    }
 
-        Some(output)
+    // Read the Lehmer code off the factorial number system representation
+    // of `rank`, from the least significant digit.
+    let mut lehmer_code = vec![0; n_objects];
+    let mut rank = rank;
+    for (i, digit) in lehmer_code.iter_mut().enumerate().rev() {
+        let base = (n_objects - i) as u64;
+        *digit = (rank % base) as usize;
+        rank /= base;
     }
+
+    let mut unused = Vec::from_iter(0..n_objects);
+    Ok(Vec::from_iter(
+        lehmer_code.into_iter().map(|digit| unused.remove(digit)),
+    ))
+}
+
+/// A permutation of `0..n_objects` drawn uniformly at random.
+pub fn random_permutation<R: Rng + ?Sized>(n_objects: usize, rng: &mut R) -> Vec<usize> {
+    let mut permutation = Vec::from_iter(0..n_objects);
+    permutation.shuffle(rng);
+    permutation
 }
 
 /// Given a permutation, writes its inverse to `output_buffer`.
@@ -139,7 +284,139 @@ impl<'a, 'i> Iterator for KnuthL<'a, 'i> {
 /// It is up to the caller to guarantee that the input
 /// to this function is an actual permutation.
 pub fn inverse_permutation(permutation: &[usize], output_buffer: &mut [usize]) {
-    for (i, n) in permutation.into_iter().map(|n| *n).enumerate() {
+    for (i, n) in permutation.iter().copied().enumerate() {
         output_buffer[n] = i;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{
+        n_permutations, random_permutation, rank_permutation, unrank_permutation, HeapPermutations,
+        PermutationsIter,
+    };
+    use crate::{
+        permutation::{inverse_permutation, DefaultSwapSchedule, SwapSchedule},
+        utilities::seeded_rng::test_rng,
+    };
+
+    #[test]
+    fn test_lexicographic_permutations_and_ranks() {
+        const N_OBJECTS: usize = 5;
+
+        let mut previous: Option<Vec<usize>> = None;
+        let mut count = 0;
+        PermutationsIter::new(N_OBJECTS).for_each(|permutation| {
+            assert!(previous.as_deref() < Some(permutation));
+            assert_eq!(rank_permutation(permutation), Ok(count));
+            assert_eq!(
+                unrank_permutation(N_OBJECTS, count).as_deref(),
+                Ok(permutation)
+            );
+            previous = Some(permutation.to_vec());
+            count += 1;
+        });
+        assert_eq!(Some(count), n_permutations(N_OBJECTS));
+
+        assert_eq!(PermutationsIter::new(0).next_permutation(), Some(&[][..]));
+        assert!(unrank_permutation(N_OBJECTS, count).is_err());
+        assert!(rank_permutation(&[0, 2, 2]).is_err());
+        assert!(rank_permutation(&[0, 3, 1]).is_err());
+    }
+
+    #[test]
+    fn test_rank_ranges() {
+        const N_OBJECTS: usize = 6;
+
+        let mut ranks = vec![];
+        for mut part in PermutationsIter::split(N_OBJECTS, 7).expect("6! fits in a u64") {
+            while let Some(permutation) = part.next_permutation() {
+                ranks.push(rank_permutation(permutation).expect("it is a permutation"));
+            }
+        }
+        assert_eq!(ranks, Vec::from_iter(0..720));
+
+        assert!(PermutationsIter::with_rank_range(N_OBJECTS, 700..721).is_err());
+        assert!(PermutationsIter::with_rank_range(21, 0..1).is_err());
+        let mut empty =
+            PermutationsIter::with_rank_range(N_OBJECTS, 720..720).expect("the range is valid");
+        assert_eq!(empty.next_permutation(), None);
+    }
+
+    #[test]
+    fn test_heap_permutations() {
+        const N_OBJECTS: usize = 6;
+
+        let mut permutations = HeapPermutations::new(N_OBJECTS);
+        let mut seen = HashSet::new();
+        let mut previous: Vec<usize> = vec![];
+        while let Some(permutation) = permutations.next_permutation() {
+            let permutation = permutation.to_vec();
+            match permutations.last_swap() {
+                None => assert!(previous.is_empty()),
+                Some((i, j)) => {
+                    previous.swap(i, j);
+                    assert_eq!(previous, permutation);
+                }
+            }
+            assert!(seen.insert(permutation.clone()));
+            previous = permutation;
+        }
+        assert_eq!(Some(seen.len() as u64), n_permutations(N_OBJECTS));
+        assert_eq!(permutations.last_swap(), None);
+    }
+
+    #[test]
+    fn test_random_permutation() {
+        let mut rng = test_rng();
+        for n_objects in 0..=20 {
+            let permutation = random_permutation(n_objects, &mut rng);
+            assert!(rank_permutation(&permutation).is_ok());
+        }
+
+        // Every permutation of 3 objects is drawn about as often as the others.
+        // The expected count is 1000, with a standard deviation of about 29.
+        const N_SAMPLES: usize = 6000;
+        let mut counts = [0usize; 6];
+        for _ in 0..N_SAMPLES {
+            let permutation = random_permutation(3, &mut rng);
+            counts[rank_permutation(&permutation).unwrap() as usize] += 1;
+        }
+        for (rank, &count) in counts.iter().enumerate() {
+            assert!(
+                (800..=1200).contains(&count),
+                "the permutation of rank {rank} is drawn {count} times out of {N_SAMPLES}"
+            );
+        }
+    }
+
+    /// Checks that `DefaultSwapSchedule` routes every permutation of 7
+    /// objects, splitting the work among some threads.
+    #[test]
+    fn test_parallel_swap_schedule_check() {
+        const N_OBJECTS: usize = 7;
+
+        let parts = PermutationsIter::split(N_OBJECTS, 4).expect("7! fits in a u64");
+        std::thread::scope(|scope| {
+            for part in parts {
+                scope.spawn(move || {
+                    part.for_each(|permutation| {
+                        let mut inverse = vec![0; N_OBJECTS];
+                        inverse_permutation(permutation, &mut inverse);
+                        let mut routed = Vec::from_iter(0..N_OBJECTS);
+                        for (selector, idx1, idx2) in
+                            DefaultSwapSchedule::permutation_to_swap_schedule(&mut inverse)
+                        {
+                            if selector {
+                                routed.swap(idx1, idx2);
+                            }
+                        }
+                        assert_eq!(routed, permutation);
+                    })
+                });
+            }
+        });
+    }
+}
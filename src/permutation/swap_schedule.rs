@@ -92,7 +92,7 @@ fn recursive_permutation_to_2_split_schedule(
         _ => (),
     }
 
-    let split_idx = (len + 1) / 2;
+    let split_idx = len.div_ceil(2);
     let part2_offset = offset + split_idx;
     let (part1, part2) = permutation.split_at_mut(split_idx);
     // we track the indices `i` such that `part2[i]` "belongs to part1".
@@ -141,11 +141,11 @@ fn recursive_permutation_to_2_split_schedule(
                     prepare_part1[idx1] = idx2;
                     prepare_part1[idx2] = idx1;
 
-                    idx1 = idx1 + 1;
+                    idx1 += 1;
                     break;
                 }
 
-                idx1 = idx1 + 1;
+                idx1 += 1;
             }
         }
     }
@@ -157,8 +157,8 @@ fn recursive_permutation_to_2_split_schedule(
     let mut prepare_part1_schedule =
         recursive_permutation_to_2_split_schedule(&mut prepare_part1, 0, vec![]);
     for (_, i, j) in prepare_part1_schedule.iter_mut() {
-        *i = *i + offset;
-        *j = *j + offset;
+        *i += offset;
+        *j += offset;
     }
     out.extend_from_slice(&prepare_part1_schedule);
 
@@ -184,9 +184,7 @@ fn recursive_permutation_to_2_split_schedule(
 
     //then, we separately append the permutation schedule for `part1` and `part2`
     let out = recursive_permutation_to_2_split_schedule(part1, offset, out);
-    let out = recursive_permutation_to_2_split_schedule(part2, part2_offset, out);
-
-    out
+    recursive_permutation_to_2_split_schedule(part2, part2_offset, out)
 }
 
 #[test]
@@ -0,0 +1,215 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use super::{PermutationsIter, SwapSchedule};
+
+/// The largest number of objects for which `check_swap_schedule` routes
+/// every permutation, since there are `n_objects!` of them.
+pub const MAX_EXHAUSTIVE_OBJECTS: usize = 8;
+
+/// The shape of the swap sequence of a `SwapSchedule` on `n_objects` objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapScheduleReport {
+    pub n_objects: usize,
+    /// The number of swaps, i.e. of swap selectors in the circuit.
+    pub length: usize,
+    /// The number of layers of swaps, where the swaps of a layer touch
+    /// disjoint positions and could be applied at the same time.
+    pub depth: usize,
+}
+
+/// A way in which a `SwapSchedule` does not behave as the trait promises.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapScheduleViolation {
+    /// The positions of the swaps returned by `permutation_to_swap_schedule`
+    /// for `input` differ from the ones of `get_swap_sequence`.
+    SequenceMismatch { input: Vec<usize> },
+    /// Applying the selected swaps to `permutation` does not sort it.
+    WrongRouting { permutation: Vec<usize> },
+    /// `permutation_to_swap_schedule` panics on `input`.
+    Panic { input: Vec<usize> },
+}
+
+/// Checks that the swap schedule `S` behaves as `SwapSchedule` promises
+/// on `n_objects` objects:
+///
+/// - for every permutation, the selected swaps sort the permutation,
+///   and their positions are the ones of `S::get_swap_sequence`,
+/// - some inputs that are not permutations, like repeated or out of range
+///   items, do not make `S::permutation_to_swap_schedule` panic, and still
+///   yield swaps at the positions of `S::get_swap_sequence`, so that the
+///   witness generation of a circuit does not crash.
+///
+/// Panics if `n_objects > MAX_EXHAUSTIVE_OBJECTS`.
+pub fn check_swap_schedule<S: SwapSchedule>(
+    n_objects: usize,
+) -> Result<SwapScheduleReport, SwapScheduleViolation> {
+    assert!(
+        n_objects <= MAX_EXHAUSTIVE_OBJECTS,
+        "routing all the permutations of {n_objects} objects takes too long"
+    );
+
+    let swap_sequence = S::get_swap_sequence(n_objects);
+
+    let mut permutations = PermutationsIter::new(n_objects);
+    while let Some(permutation) = permutations.next_permutation() {
+        let routed = schedule_swaps::<S>(permutation, &swap_sequence)?;
+        if routed.iter().copied().ne(0..n_objects) {
+            return Err(SwapScheduleViolation::WrongRouting {
+                permutation: permutation.to_vec(),
+            });
+        }
+    }
+
+    for input in non_permutations(n_objects) {
+        schedule_swaps::<S>(&input, &swap_sequence)?;
+    }
+
+    Ok(SwapScheduleReport {
+        n_objects,
+        length: swap_sequence.len(),
+        depth: swap_sequence_depth(&swap_sequence),
+    })
+}
+
+/// Runs `S::permutation_to_swap_schedule` on a copy of `input`, checks the
+/// positions of the swaps against `swap_sequence`, and returns `input`
+/// with the selected swaps applied.
+fn schedule_swaps<S: SwapSchedule>(
+    input: &[usize],
+    swap_sequence: &[(usize, usize)],
+) -> Result<Vec<usize>, SwapScheduleViolation> {
+    let schedule = catch_unwind(AssertUnwindSafe(|| {
+        S::permutation_to_swap_schedule(&mut input.to_vec())
+    }))
+    .map_err(|_| SwapScheduleViolation::Panic {
+        input: input.to_vec(),
+    })?;
+
+    if schedule
+        .iter()
+        .map(|&(_selector, idx1, idx2)| (idx1, idx2))
+        .ne(swap_sequence.iter().copied())
+    {
+        return Err(SwapScheduleViolation::SequenceMismatch {
+            input: input.to_vec(),
+        });
+    }
+
+    let mut routed = input.to_vec();
+    for (selector, idx1, idx2) in schedule {
+        if selector {
+            routed.swap(idx1, idx2);
+        }
+    }
+    Ok(routed)
+}
+
+/// Some inputs of length `n_objects` that are not permutations.
+fn non_permutations(n_objects: usize) -> Vec<Vec<usize>> {
+    if n_objects == 0 {
+        return vec![];
+    }
+    let mut inputs = vec![
+        vec![0; n_objects],
+        vec![n_objects - 1; n_objects],
+        Vec::from_iter((0..n_objects).map(|i| i + n_objects)),
+        Vec::from_iter((0..n_objects).rev().map(|i| i * n_objects)),
+        Vec::from_iter((0..n_objects).map(|i| i / 2)),
+        Vec::from_iter((0..n_objects).map(|i| usize::MAX - i)),
+    ];
+    if n_objects > 1 {
+        // A permutation with one repeated item.
+        let mut repeated = Vec::from_iter((0..n_objects).rev());
+        repeated[0] = repeated[1];
+        inputs.push(repeated);
+    }
+    inputs
+}
+
+/// The number of layers of `swap_sequence`, where every swap is placed in
+/// the first layer after the last swap that touches one of its positions.
+pub fn swap_sequence_depth(swap_sequence: &[(usize, usize)]) -> usize {
+    let n_positions = swap_sequence
+        .iter()
+        .map(|&(idx1, idx2)| idx1.max(idx2) + 1)
+        .max()
+        .unwrap_or(0);
+    // The layer of the last swap that touched each position.
+    let mut layers = vec![0; n_positions];
+    swap_sequence.iter().fold(0, |depth, &(idx1, idx2)| {
+        let layer = layers[idx1].max(layers[idx2]) + 1;
+        layers[idx1] = layer;
+        layers[idx2] = layer;
+        depth.max(layer)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_swap_schedule, swap_sequence_depth, SwapScheduleViolation, MAX_EXHAUSTIVE_OBJECTS,
+    };
+    use crate::permutation::{BubbleSortSwapSchedule, RecusriveSplitTwoSchedule, SwapSchedule};
+
+    #[test]
+    fn test_bubble_sort_schedule_conformance() {
+        for n_objects in 0..=MAX_EXHAUSTIVE_OBJECTS {
+            let report = check_swap_schedule::<BubbleSortSwapSchedule>(n_objects)
+                .expect("the bubble sort schedule is a valid schedule");
+            assert_eq!(report.length, n_objects * n_objects.saturating_sub(1) / 2);
+            // the bubble sort network has depth 2 * n_objects - 3
+            assert_eq!(report.depth, (2 * n_objects).saturating_sub(3));
+        }
+    }
+
+    #[test]
+    fn test_recursive_split_two_schedule_conformance() {
+        for n_objects in 0..=MAX_EXHAUSTIVE_OBJECTS {
+            let report = check_swap_schedule::<RecusriveSplitTwoSchedule>(n_objects)
+                .expect("the recursive split two schedule is a valid schedule");
+            assert!(report.depth <= report.length);
+            if n_objects > 4 {
+                assert!(report.length < n_objects * (n_objects - 1) / 2);
+            }
+        }
+    }
+
+    /// A schedule that forgets the last swap of the bubble sort schedule.
+    enum TruncatedSchedule {}
+
+    impl SwapSchedule for TruncatedSchedule {
+        fn permutation_to_swap_schedule(permutation: &mut [usize]) -> Vec<(bool, usize, usize)> {
+            let mut schedule = BubbleSortSwapSchedule::permutation_to_swap_schedule(permutation);
+            schedule.pop();
+            schedule
+        }
+    }
+
+    /// A schedule that panics on inputs that are not permutations.
+    enum PickySchedule {}
+
+    impl SwapSchedule for PickySchedule {
+        fn permutation_to_swap_schedule(permutation: &mut [usize]) -> Vec<(bool, usize, usize)> {
+            let mut seen = vec![false; permutation.len()];
+            for &item in permutation.iter() {
+                assert!(!std::mem::replace(&mut seen[item], true));
+            }
+            BubbleSortSwapSchedule::permutation_to_swap_schedule(permutation)
+        }
+    }
+
+    #[test]
+    fn test_schedule_violations() {
+        assert_eq!(
+            check_swap_schedule::<TruncatedSchedule>(3),
+            Err(SwapScheduleViolation::WrongRouting {
+                permutation: vec![1, 2, 0]
+            })
+        );
+        assert!(matches!(
+            check_swap_schedule::<PickySchedule>(3),
+            Err(SwapScheduleViolation::Panic { .. })
+        ));
+        assert_eq!(swap_sequence_depth(&[(0, 1), (2, 3), (1, 2), (0, 1)]), 3);
+    }
+}
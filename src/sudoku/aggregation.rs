@@ -0,0 +1,359 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, ensure};
+use plonky2::{
+    field::types::{Field, PrimeField64},
+    hash::{
+        hash_types::{HashOut, HashOutTarget, NUM_HASH_OUT_ELTS},
+        poseidon::PoseidonHash,
+    },
+    iop::{
+        target::BoolTarget,
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, Hasher, PoseidonGoldilocksConfig},
+        proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget},
+    },
+};
+
+use super::{SudokuCircuitBuilder, SudokuProblemTarget, SudokuWitnessBuilder};
+use crate::{
+    utilities::metrics::{prove_with_metrics, verify_with_metrics, Phase},
+    verifier_bundle::VerifierBundle,
+};
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+/// The height of the tallest aggregation tree we support.
+/// A tree of height `h` aggregates up to `2^h` proofs.
+const MAX_AGGREGATION_HEIGHT: usize = 32;
+
+/// The output of `SudokuAggregator::aggregate`.
+///
+/// The public inputs of `proof` are the 4 elements of the commitment
+/// to the list of aggregated puzzles, as computed by
+/// `SudokuAggregator::puzzles_commitment`, followed by the number of puzzles.
+pub struct AggregatedSudokuProof {
+    /// The height of the aggregation tree. It determines which
+    /// circuit is needed to verify `proof`.
+    pub height: usize,
+    pub proof: ProofWithPublicInputs<BaseField, PGConfig, D>,
+}
+
+impl AggregatedSudokuProof {
+    /// The commitment to the list of puzzles whose solution is proven.
+    pub fn commitment(&self) -> HashOut<BaseField> {
+        HashOut::from_partial(&self.proof.public_inputs[..NUM_HASH_OUT_ELTS])
+    }
+
+    /// The number of puzzles whose solution is proven.
+    pub fn n_puzzles(&self) -> u64 {
+        self.proof.public_inputs[NUM_HASH_OUT_ELTS].to_canonical_u64()
+    }
+}
+
+/// The hash that takes the place of the missing right child when a level
+/// of the aggregation tree contains an odd number of proofs.
+const PADDING_HASH: HashOut<BaseField> = HashOut::ZERO;
+
+/// A circuit that verifies two proofs of the level below,
+/// and commits to the puzzles they prove the solution of.
+struct AggregationCircuit {
+    circuit_data: CircuitData<BaseField, PGConfig, D>,
+    left_proof_target: ProofWithPublicInputsTarget<D>,
+    right_proof_target: ProofWithPublicInputsTarget<D>,
+    /// Whether the right proof is a copy of the left one, which only
+    /// pads a level with an odd number of proofs.
+    right_is_padding_target: BoolTarget,
+}
+
+/// Aggregates many Sudoku proofs into a single one.
+///
+/// The proofs to aggregate are produced by the circuit returned by
+/// `leaf_circuit`, which proves the knowledge of a solution for a Sudoku
+/// puzzle, and exposes the puzzle as public inputs, as in
+/// `SudokuProblemTarget::register_problem_public_inputs`.
+///
+/// Proofs are verified pairwise in a binary tree of recursive proofs.
+/// At the first level of the tree, each puzzle is hashed with Poseidon,
+/// then at every level the two hashes of the children are hashed together.
+/// When a level contains an odd number of proofs, the last one is paired
+/// with itself, but its hash is replaced by a constant, so that lists that
+/// only differ by the repetition of their last puzzles have different
+/// commitments. The public inputs of the aggregated proof are the root of
+/// the tree and the number of aggregated puzzles.
+pub struct SudokuAggregator<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize = BOX_ROWS,
+> {
+    leaf_circuit_data: CircuitData<BaseField, PGConfig, D>,
+    leaf_target: SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+    circuit_config: CircuitConfig,
+    // `aggregation_circuits[h - 1]` is the circuit of the `h`-th level of
+    // the aggregation tree. Circuits are built only when they are needed.
+    aggregation_circuits: Vec<OnceLock<AggregationCircuit>>,
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuAggregator<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Builds the circuit for the individual Sudoku proofs.
+    /// All the circuits of the aggregation tree will use `circuit_config`.
+    pub fn new(circuit_config: CircuitConfig) -> Result<Self, ()> {
+        let mut builder = CircuitBuilder::<BaseField, D>::new(circuit_config.clone());
+        let leaf_target =
+            SudokuCircuitBuilder::<SIZE, BOX_ROWS, BOX_COLS>::add_proof_of_sudoku_solution(
+                &mut builder,
+            )?;
+        leaf_target.register_problem_public_inputs(&mut builder);
+
+        Ok(Self {
+            leaf_circuit_data: crate::time_it! {
+                builder.build();
+                Phase::CircuitBuild, "sudoku aggregation/leaf"
+            },
+            leaf_target,
+            circuit_config,
+            aggregation_circuits: Vec::from_iter(
+                (0..MAX_AGGREGATION_HEIGHT).map(|_| OnceLock::new()),
+            ),
+        })
+    }
+
+    /// The circuit whose proofs can be aggregated.
+    pub fn leaf_circuit(&self) -> &CircuitData<BaseField, PGConfig, D> {
+        &self.leaf_circuit_data
+    }
+
+    /// Proves the knowledge of `solution` for `problem`, using the
+    /// circuit returned by `leaf_circuit`.
+    pub fn prove_solution(
+        &self,
+        problem: [[usize; SIZE]; SIZE],
+        solution: [[usize; SIZE]; SIZE],
+    ) -> anyhow::Result<ProofWithPublicInputs<BaseField, PGConfig, D>> {
+        let mut witness = PartialWitness::new();
+        SudokuWitnessBuilder::set_sudoku_witness(
+            &mut witness,
+            &self.leaf_target,
+            problem,
+            solution,
+        );
+        prove_with_metrics(&self.leaf_circuit_data, witness, "sudoku aggregation/leaf")
+    }
+
+    /// Aggregates `proofs`, which must have been produced by the
+    /// circuit returned by `leaf_circuit`.
+    ///
+    /// Fails if `proofs` is empty or too long, or if one of the proofs
+    /// is not valid.
+    pub fn aggregate(
+        &self,
+        proofs: Vec<ProofWithPublicInputs<BaseField, PGConfig, D>>,
+    ) -> anyhow::Result<AggregatedSudokuProof> {
+        ensure!(!proofs.is_empty(), "there are no proofs to aggregate");
+        ensure!(
+            proofs.len().ilog2() < MAX_AGGREGATION_HEIGHT as u32,
+            "too many proofs to aggregate"
+        );
+
+        // We check the proofs beforehand, so that we can point out
+        // which one is invalid.
+        for (idx, proof) in proofs.iter().enumerate() {
+            self.leaf_circuit_data
+                .verify(proof.clone())
+                .map_err(|err| anyhow!("proof number {idx} is not valid: {err}"))?;
+        }
+
+        let mut height = 0;
+        let mut level_proofs = proofs;
+        // We need at least one aggregation step, so that the output always
+        // comes from one of the aggregation circuits.
+        while height == 0 || level_proofs.len() > 1 {
+            height += 1;
+            let circuit = self.aggregation_circuit(height);
+
+            level_proofs = level_proofs
+                .chunks(2)
+                .map(|pair| {
+                    let mut witness = PartialWitness::new();
+                    witness.set_proof_with_pis_target(&circuit.left_proof_target, &pair[0]);
+                    witness.set_proof_with_pis_target(
+                        &circuit.right_proof_target,
+                        pair.last().expect("chunks are never empty"),
+                    );
+                    witness.set_bool_target(circuit.right_is_padding_target, pair.len() == 1);
+                    prove_with_metrics(&circuit.circuit_data, witness, "sudoku aggregation")
+                })
+                .collect::<anyhow::Result<_>>()?;
+        }
+
+        Ok(AggregatedSudokuProof {
+            height,
+            proof: level_proofs
+                .pop()
+                .expect("the loop ends when one proof is left"),
+        })
+    }
+
+    /// Verifies an aggregated proof with the circuit of height
+    /// `aggregated_proof.height`.
+    ///
+    /// The height is provided by the prover, so it is checked against the
+    /// number of puzzles, which is a public input: a valid proof for `n`
+    /// puzzles always has height `aggregation_height(n)`.
+    /// The caller still has to compare `commitment` and `n_puzzles` with
+    /// the list of puzzles it expects.
+    pub fn verify(&self, aggregated_proof: AggregatedSudokuProof) -> anyhow::Result<()> {
+        ensure!(
+            (1..=MAX_AGGREGATION_HEIGHT).contains(&aggregated_proof.height),
+            "invalid aggregation tree height"
+        );
+        ensure!(
+            aggregated_proof.proof.public_inputs.len() == NUM_HASH_OUT_ELTS + 1,
+            "the proof does not have the public inputs of an aggregated proof"
+        );
+        let n_puzzles = aggregated_proof.n_puzzles();
+        ensure!(
+            n_puzzles >= 1 && aggregation_height(n_puzzles) == aggregated_proof.height,
+            "the height {} does not match the number of puzzles {n_puzzles}",
+            aggregated_proof.height
+        );
+        verify_with_metrics(
+            &self
+                .aggregation_circuit(aggregated_proof.height)
+                .circuit_data,
+            aggregated_proof.proof,
+            "sudoku aggregation",
+        )
+    }
+
+    /// The verifier bundle for the aggregated proofs of height `height`,
+    /// i.e. the ones that aggregate between `2^(height - 1) + 1`
+    /// and `2^height` proofs, or 1 or 2 proofs if `height == 1`.
+    pub fn verifier_bundle(&self, height: usize) -> VerifierBundle<BaseField, PGConfig, D> {
+        assert!((1..=MAX_AGGREGATION_HEIGHT).contains(&height));
+        VerifierBundle::from_circuit_data(&self.aggregation_circuit(height).circuit_data)
+    }
+
+    /// Computes the commitment to a list of puzzles that is exposed by
+    /// the aggregated proof of their solutions.
+    ///
+    /// Empty cells are represented by `0`.
+    pub fn puzzles_commitment(puzzles: &[[[usize; SIZE]; SIZE]]) -> HashOut<BaseField> {
+        assert!(!puzzles.is_empty());
+
+        let mut hashes = Vec::from_iter(puzzles.iter().map(|puzzle| {
+            PoseidonHash::hash_no_pad(&Vec::from_iter(
+                puzzle
+                    .iter()
+                    .flatten()
+                    .map(|&n| BaseField::from_canonical_usize(n)),
+            ))
+        }));
+
+        // This loop mirrors the one in `aggregate`.
+        let mut height = 0;
+        while height == 0 || hashes.len() > 1 {
+            height += 1;
+            hashes = Vec::from_iter(hashes.chunks(2).map(|pair| {
+                let (left, right) = (pair[0], *pair.get(1).unwrap_or(&PADDING_HASH));
+                PoseidonHash::hash_no_pad(&[left.elements, right.elements].concat())
+            }));
+        }
+
+        hashes[0]
+    }
+
+    fn aggregation_circuit(&self, height: usize) -> &AggregationCircuit {
+        self.aggregation_circuits[height - 1].get_or_init(|| {
+            let inner_circuit_data = if height == 1 {
+                &self.leaf_circuit_data
+            } else {
+                &self.aggregation_circuit(height - 1).circuit_data
+            };
+            crate::time_it! {
+                self.build_aggregation_circuit(height, inner_circuit_data);
+                Phase::CircuitBuild, "sudoku aggregation"
+            }
+        })
+    }
+
+    fn build_aggregation_circuit(
+        &self,
+        height: usize,
+        inner_circuit_data: &CircuitData<BaseField, PGConfig, D>,
+    ) -> AggregationCircuit {
+        let mut builder = CircuitBuilder::<BaseField, D>::new(self.circuit_config.clone());
+
+        let inner_verifier_target =
+            builder.constant_verifier_data(&inner_circuit_data.verifier_only);
+
+        let [left_proof_target, right_proof_target] = [(); 2].map(|_| {
+            let proof_target = builder.add_virtual_proof_with_pis(&inner_circuit_data.common);
+            builder.verify_proof::<PGConfig>(
+                &proof_target,
+                &inner_verifier_target,
+                &inner_circuit_data.common,
+            );
+            proof_target
+        });
+
+        // At the first level of the tree, the public inputs of the inner
+        // proofs are the puzzles, and we have to hash them. At the other
+        // levels, they are already hashes, followed by the number of puzzles.
+        let [(left_hash, left_count), (right_hash, right_count)] =
+            [&left_proof_target, &right_proof_target].map(|proof| {
+                if height == 1 {
+                    (
+                        builder.hash_n_to_hash_no_pad::<PoseidonHash>(proof.public_inputs.clone()),
+                        builder.one(),
+                    )
+                } else {
+                    (
+                        HashOutTarget::from_vec(proof.public_inputs[..NUM_HASH_OUT_ELTS].to_vec()),
+                        proof.public_inputs[NUM_HASH_OUT_ELTS],
+                    )
+                }
+            });
+
+        let right_is_padding_target = builder.add_virtual_bool_target_safe();
+        let padding_hash = builder.constant_hash(PADDING_HASH);
+        let right_hash = HashOutTarget::from(core::array::from_fn(|i| {
+            builder.select(
+                right_is_padding_target,
+                padding_hash.elements[i],
+                right_hash.elements[i],
+            )
+        }));
+        let right_is_real = builder.not(right_is_padding_target);
+        let right_count = builder.mul(right_count, right_is_real.target);
+        let n_puzzles = builder.add(left_count, right_count);
+
+        let commitment = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+            [left_hash.elements, right_hash.elements].concat(),
+        );
+        builder.register_public_inputs(&commitment.elements);
+        builder.register_public_input(n_puzzles);
+
+        AggregationCircuit {
+            circuit_data: builder.build(),
+            left_proof_target,
+            right_proof_target,
+            right_is_padding_target,
+        }
+    }
+}
+
+/// The height of the aggregation tree of `n_puzzles` proofs: the number
+/// of times the proofs are halved, rounding up, until one is left, but
+/// at least 1.
+pub fn aggregation_height(n_puzzles: u64) -> usize {
+    n_puzzles.next_power_of_two().ilog2().max(1) as usize
+}
@@ -0,0 +1,97 @@
+use plonky2::{
+    iop::witness::PartialWitness,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+        proof::ProofWithPublicInputs,
+    },
+};
+
+use super::{SudokuCircuitBuilder, SudokuProblemTarget, SudokuWitnessBuilder};
+use crate::utilities::connect_strategy::{ConnectStrategy, CopyConstraint};
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+/// A circuit that proves the knowledge of the solutions of `N` Sudoku
+/// puzzles with a single proof.
+///
+/// Unlike `SudokuAggregator`, no recursion is involved: the constraints of
+/// the `N` puzzles live side by side in the same circuit, and they share the
+/// constants of the symbols. Since the cost of a proof grows slower than the
+/// number of gates of its circuit, proving `N` puzzles at once is cheaper
+/// than producing `N` single-puzzle proofs.
+///
+/// The public inputs of a proof are the `N` problem grids, in order,
+/// each one laid out as in `SudokuProblemTarget::register_problem_public_inputs`.
+pub struct SudokuBatchCircuit<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const N: usize,
+    const BOX_COLS: usize = BOX_ROWS,
+> {
+    circuit_data: CircuitData<BaseField, PGConfig, D>,
+    targets: [SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>; N],
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const N: usize, const BOX_COLS: usize>
+    SudokuBatchCircuit<SIZE, BOX_ROWS, N, BOX_COLS>
+{
+    pub fn new(circuit_config: CircuitConfig) -> Result<Self, ()> {
+        Self::new_with_connect_strategy(circuit_config, CopyConstraint)
+    }
+
+    /// Like `new`, with the equalities of every puzzle enforced
+    /// through `connect_strategy`.
+    ///
+    /// With `TestEq`, proving an invalid solution does not panic,
+    /// and yields a proof that fails verification instead.
+    pub fn new_with_connect_strategy(
+        circuit_config: CircuitConfig,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<Self, ()> {
+        let mut builder = CircuitBuilder::<BaseField, D>::new(circuit_config);
+        let targets = SudokuCircuitBuilder::<SIZE, BOX_ROWS, BOX_COLS>::add_proof_of_sudoku_solutions_with_connect_strategy(
+            &mut builder,
+            connect_strategy,
+        )?;
+        for target in targets.iter() {
+            target.register_problem_public_inputs(&mut builder);
+        }
+
+        Ok(Self {
+            circuit_data: builder.build(),
+            targets,
+        })
+    }
+
+    pub fn circuit_data(&self) -> &CircuitData<BaseField, PGConfig, D> {
+        &self.circuit_data
+    }
+
+    /// Proves the knowledge of `solutions[i]` for `problems[i]`,
+    /// for every `i < N`.
+    pub fn prove(
+        &self,
+        problems: [[[usize; SIZE]; SIZE]; N],
+        solutions: [[[usize; SIZE]; SIZE]; N],
+    ) -> anyhow::Result<ProofWithPublicInputs<BaseField, PGConfig, D>> {
+        let mut witness = PartialWitness::new();
+        SudokuWitnessBuilder::set_sudoku_batch_witness(
+            &mut witness,
+            &self.targets,
+            problems,
+            solutions,
+        );
+        self.circuit_data.prove(witness)
+    }
+
+    pub fn verify(
+        &self,
+        proof: ProofWithPublicInputs<BaseField, PGConfig, D>,
+    ) -> anyhow::Result<()> {
+        self.circuit_data.verify(proof)
+    }
+}
@@ -0,0 +1,116 @@
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{
+        target::{BoolTarget, Target},
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+use super::{SudokuCircuitBuilder, SudokuProblemTarget, SudokuWitnessBuilder};
+
+/// The targets added by `SudokuProblemTarget::add_cell_disclosure`.
+///
+/// `mask` is public, and `disclosed[i][j]` is computed in-circuit as
+/// `mask[i][j] * solution[i][j]`. Since the mask is constrained to be
+/// boolean, the disclosed grid contains the masked cells of the solution,
+/// and `0` everywhere else.
+pub struct CellDisclosureTarget<const SIZE: usize> {
+    pub mask: [[BoolTarget; SIZE]; SIZE],
+    pub disclosed: [[Target; SIZE]; SIZE],
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Registers the mask grid as public inputs, followed by the disclosed
+    /// cells of the solution. Both grids are read row by row.
+    /// See `CellDisclosureTarget` for the details.
+    pub fn add_cell_disclosure<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> CellDisclosureTarget<SIZE> {
+        let mask = core::array::from_fn(|_| {
+            core::array::from_fn(|_| builder.add_virtual_bool_target_safe())
+        });
+        for row in mask.iter() {
+            builder.register_public_inputs(&row.map(|cell: BoolTarget| cell.target));
+        }
+
+        let disclosed = core::array::from_fn(|row| {
+            core::array::from_fn(|col| builder.mul(mask[row][col].target, self.solution[row][col]))
+        });
+        for row in disclosed.iter() {
+            builder.register_public_inputs(row);
+        }
+
+        CellDisclosureTarget { mask, disclosed }
+    }
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuCircuitBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Proves the knowledge of a solution for the problem, revealing only
+    /// the cells of the solution selected by a public mask.
+    ///
+    /// The public inputs are the problem grid, followed by the mask and by
+    /// the disclosed cells, as in `SudokuProblemTarget::add_cell_disclosure`.
+    pub fn add_proof_of_sudoku_solution_with_disclosure<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<
+        (
+            SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+            CellDisclosureTarget<SIZE>,
+        ),
+        (),
+    > {
+        let sudoku_target = Self::add_proof_of_sudoku_solution(builder)?;
+        sudoku_target.register_problem_public_inputs(builder);
+        let disclosure_target = sudoku_target.add_cell_disclosure(builder);
+        Ok((sudoku_target, disclosure_target))
+    }
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuWitnessBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Sets the mask of a circuit built with
+    /// `SudokuProblemTarget::add_cell_disclosure`.
+    ///
+    /// The disclosed cells are computed by the circuit.
+    pub fn set_cell_disclosure_witness<F: RichField>(
+        witness: &mut PartialWitness<F>,
+        disclosure_target: &CellDisclosureTarget<SIZE>,
+        mask: [[bool; SIZE]; SIZE],
+    ) {
+        for (target_row, mask_row) in disclosure_target.mask.iter().zip(mask) {
+            for (target, value) in target_row.iter().zip(mask_row) {
+                witness.set_bool_target(*target, value);
+            }
+        }
+    }
+
+    /// Computes, outside of the circuit, the disclosed grid exposed by a
+    /// circuit built with `SudokuProblemTarget::add_cell_disclosure`.
+    ///
+    /// Cells that are not disclosed are represented by `0`.
+    pub fn disclosed_cells(
+        solution: [[usize; SIZE]; SIZE],
+        mask: [[bool; SIZE]; SIZE],
+    ) -> [[usize; SIZE]; SIZE] {
+        core::array::from_fn(|row| {
+            core::array::from_fn(|col| {
+                if mask[row][col] {
+                    solution[row][col]
+                } else {
+                    0
+                }
+            })
+        })
+    }
+}
@@ -1,15 +1,46 @@
-use plonky2::iop::target::Target;
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+    plonk::circuit_builder::CircuitBuilder,
+};
 
 mod sudoku_circuit_builder;
-pub use sudoku_circuit_builder::SudokuCircuitBuilder;
+pub use sudoku_circuit_builder::{SudokuCircuitBuilder, SudokuConstraintStrategy};
 
 mod sudoku_witness_builder;
 pub use sudoku_witness_builder::SudokuWitnessBuilder;
 
+mod aggregation;
+pub use aggregation::{aggregation_height, AggregatedSudokuProof, SudokuAggregator};
+
+mod batch;
+pub use batch::SudokuBatchCircuit;
+
+mod prover_binding;
+pub use prover_binding::ProverBindingTarget;
+
+mod cell_disclosure;
+pub use cell_disclosure::CellDisclosureTarget;
+
+mod solution_commitment;
+pub use solution_commitment::SolutionCommitmentTarget;
+
+mod symmetry;
+pub use symmetry::{SudokuEquivalenceTarget, SudokuSymmetry};
+
 #[cfg(test)]
 mod tests;
 
-pub struct SudokuProblemTarget<const SIZE: usize, const SIZE_SQRT: usize> {
+/// The targets of a proof of the solution of a `SIZE x SIZE` Sudoku puzzle,
+/// whose regions are `BOX_ROWS x BOX_COLS` rectangles.
+///
+/// `BOX_COLS` defaults to `BOX_ROWS`, so `SudokuProblemTarget<9, 3>`
+/// is the classic Sudoku, while `SudokuProblemTarget<6, 2, 3>` is the
+/// 6x6 variant with 2 rows and 3 columns in every region.
+pub struct SudokuProblemTarget<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize = BOX_ROWS,
+> {
     problem: [[Target; SIZE]; SIZE],
     solution: [[Target; SIZE]; SIZE],
     symbols: [Target; SIZE],
@@ -18,6 +49,23 @@ pub struct SudokuProblemTarget<const SIZE: usize, const SIZE_SQRT: usize> {
     region_swap_selectors: [Vec<Target>; SIZE],
 }
 
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Registers the cells of the problem grid as public inputs of the circuit,
+    /// row by row.
+    ///
+    /// Empty cells are encoded as `0`, the other ones as the symbol they contain.
+    pub fn register_problem_public_inputs<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) {
+        for row in self.problem.iter() {
+            builder.register_public_inputs(row);
+        }
+    }
+}
+
 impl<const SIZE: usize> SudokuProblemTarget<SIZE, 0> {
     pub fn get_rows<Item: Copy>(grid: &[[Item; SIZE]; SIZE]) -> [[Item; SIZE]; SIZE] {
         *grid
@@ -28,16 +76,22 @@ impl<const SIZE: usize> SudokuProblemTarget<SIZE, 0> {
     }
 }
 
-impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuProblemTarget<SIZE, SIZE_SQRT> {
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Splits the grid in `BOX_ROWS x BOX_COLS` regions. Regions are listed
+    /// row by row, and so are the cells inside each region.
     pub fn get_regions<Item: Copy>(grid: &[[Item; SIZE]; SIZE]) -> [[Item; SIZE]; SIZE] {
+        // Each horizontal band of `BOX_ROWS` rows contains
+        // `SIZE / BOX_COLS == BOX_ROWS` regions.
         core::array::from_fn::<_, SIZE, _>(|region_idx| {
             let (row_region_offset, col_region_offset) = (
-                SIZE_SQRT * (region_idx / SIZE_SQRT),
-                SIZE_SQRT * (region_idx % SIZE_SQRT),
+                BOX_ROWS * (region_idx / BOX_ROWS),
+                BOX_COLS * (region_idx % BOX_ROWS),
             );
             core::array::from_fn::<_, SIZE, _>(|cell_idx| {
-                grid[row_region_offset + cell_idx / SIZE_SQRT]
-                    [col_region_offset + cell_idx % SIZE_SQRT]
+                grid[row_region_offset + cell_idx / BOX_COLS]
+                    [col_region_offset + cell_idx % BOX_COLS]
             })
         })
     }
@@ -0,0 +1,111 @@
+use plonky2::{
+    field::extension::Extendable,
+    hash::{
+        hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS},
+        poseidon::PoseidonHash,
+    },
+    iop::{
+        target::Target,
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::{circuit_builder::CircuitBuilder, config::Hasher},
+};
+
+use super::{SudokuProblemTarget, SudokuWitnessBuilder};
+
+/// The targets added by `SudokuProblemTarget::add_prover_binding`.
+///
+/// `prover_id` and `challenge_nonce` are public inputs, followed by the
+/// 4 elements of `binding`, which is computed in-circuit as
+///
+/// ``` text
+/// Poseidon(prover_id || challenge_nonce || solution || salt)
+/// ```
+///
+/// where the solution is read row by row, and `salt` is private.
+///
+/// Since the public inputs are part of the statement being proven, a proof
+/// cannot be replayed under a different identity or for a different
+/// challenge. On top of that, `binding` gives each (prover, challenge,
+/// solution) triple a distinct tag, so that resubmissions can be detected.
+/// As for `SolutionCommitmentTarget`, the salt prevents anyone from
+/// recovering the solution by brute-forcing `binding`, so it must be sampled
+/// uniformly at random and kept secret.
+pub struct ProverBindingTarget {
+    pub prover_id: Target,
+    pub challenge_nonce: Target,
+    pub salt: [Target; NUM_HASH_OUT_ELTS],
+    pub binding: HashOutTarget,
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Binds the proof of the solution to a prover identity and a challenge
+    /// nonce, registering them as public inputs, followed by the binding.
+    /// See `ProverBindingTarget` for the details.
+    pub fn add_prover_binding<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> ProverBindingTarget {
+        let prover_id = builder.add_virtual_public_input();
+        let challenge_nonce = builder.add_virtual_public_input();
+        let salt = builder.add_virtual_target_arr();
+
+        let mut binding_inputs = Vec::with_capacity(2 + SIZE * SIZE + NUM_HASH_OUT_ELTS);
+        binding_inputs.extend([prover_id, challenge_nonce]);
+        binding_inputs.extend(self.solution.iter().flatten());
+        binding_inputs.extend(salt);
+
+        let binding = builder.hash_n_to_hash_no_pad::<PoseidonHash>(binding_inputs);
+        builder.register_public_inputs(&binding.elements);
+
+        ProverBindingTarget {
+            prover_id,
+            challenge_nonce,
+            salt,
+            binding,
+        }
+    }
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuWitnessBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Sets the prover identity, the challenge nonce and the salt
+    /// of a circuit built with `SudokuProblemTarget::add_prover_binding`.
+    ///
+    /// The binding itself is computed by the circuit.
+    pub fn set_prover_binding_witness<F: RichField>(
+        witness: &mut PartialWitness<F>,
+        binding_target: &ProverBindingTarget,
+        prover_id: F,
+        challenge_nonce: F,
+        salt: [F; NUM_HASH_OUT_ELTS],
+    ) {
+        witness.set_target(binding_target.prover_id, prover_id);
+        witness.set_target(binding_target.challenge_nonce, challenge_nonce);
+        witness.set_target_arr(&binding_target.salt, &salt);
+    }
+
+    /// Computes, outside of the circuit, the binding exposed by a circuit
+    /// built with `SudokuProblemTarget::add_prover_binding`.
+    pub fn prover_binding<F: RichField>(
+        prover_id: F,
+        challenge_nonce: F,
+        solution: [[usize; SIZE]; SIZE],
+        salt: [F; NUM_HASH_OUT_ELTS],
+    ) -> HashOut<F> {
+        let mut binding_inputs = Vec::with_capacity(2 + SIZE * SIZE + NUM_HASH_OUT_ELTS);
+        binding_inputs.extend([prover_id, challenge_nonce]);
+        binding_inputs.extend(
+            solution
+                .iter()
+                .flatten()
+                .map(|&n| F::from_canonical_usize(n)),
+        );
+        binding_inputs.extend(salt);
+
+        PoseidonHash::hash_no_pad(&binding_inputs)
+    }
+}
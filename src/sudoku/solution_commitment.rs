@@ -0,0 +1,143 @@
+use plonky2::{
+    field::extension::Extendable,
+    hash::{
+        hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS},
+        poseidon::PoseidonHash,
+    },
+    iop::{
+        target::Target,
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::{circuit_builder::CircuitBuilder, config::Hasher},
+};
+
+use super::{SudokuCircuitBuilder, SudokuProblemTarget, SudokuWitnessBuilder};
+
+/// The targets added by `SudokuProblemTarget::add_solution_commitment`.
+///
+/// `commitment` is computed in-circuit as
+///
+/// ``` text
+/// Poseidon(solution || salt)
+/// ```
+///
+/// where the solution is read row by row, and `salt` is private.
+/// The salt prevents anyone from recovering the solution by brute-forcing
+/// the commitment, so it must be sampled uniformly at random and kept secret
+/// until the solution is revealed.
+pub struct SolutionCommitmentTarget {
+    pub salt: [Target; NUM_HASH_OUT_ELTS],
+    pub commitment: HashOutTarget,
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Registers the problem grid as public inputs, followed by a commitment
+    /// to the solution. See `SolutionCommitmentTarget` for the details.
+    pub fn add_solution_commitment<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> SolutionCommitmentTarget {
+        self.register_problem_public_inputs(builder);
+
+        let salt = builder.add_virtual_target_arr();
+
+        let mut commitment_inputs = Vec::with_capacity(SIZE * SIZE + NUM_HASH_OUT_ELTS);
+        commitment_inputs.extend(self.solution.iter().flatten());
+        commitment_inputs.extend(salt);
+
+        let commitment = builder.hash_n_to_hash_no_pad::<PoseidonHash>(commitment_inputs);
+        builder.register_public_inputs(&commitment.elements);
+
+        SolutionCommitmentTarget { salt, commitment }
+    }
+
+    /// Registers the cells of the solution grid as public inputs of the circuit,
+    /// row by row.
+    pub fn register_solution_public_inputs<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) {
+        for row in self.solution.iter() {
+            builder.register_public_inputs(row);
+        }
+    }
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuCircuitBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Commit phase of the commit-reveal scheme.
+    ///
+    /// Proves the knowledge of a solution for the problem, exposing as
+    /// public inputs the problem grid, followed by the commitment to the
+    /// solution, without revealing the solution itself.
+    pub fn add_commitment_to_sudoku_solution<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<
+        (
+            SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+            SolutionCommitmentTarget,
+        ),
+        (),
+    > {
+        let sudoku_target = Self::add_proof_of_sudoku_solution(builder)?;
+        let commitment_target = sudoku_target.add_solution_commitment(builder);
+        Ok((sudoku_target, commitment_target))
+    }
+
+    /// Reveal phase of the commit-reveal scheme.
+    ///
+    /// Like `add_commitment_to_sudoku_solution`, but the solution grid is
+    /// registered as public inputs too, after the commitment. A proof for
+    /// this circuit shows that the revealed solution solves the problem,
+    /// and that it opens the commitment. The salt stays private.
+    pub fn add_reveal_of_sudoku_solution<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<
+        (
+            SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+            SolutionCommitmentTarget,
+        ),
+        (),
+    > {
+        let (sudoku_target, commitment_target) = Self::add_commitment_to_sudoku_solution(builder)?;
+        sudoku_target.register_solution_public_inputs(builder);
+        Ok((sudoku_target, commitment_target))
+    }
+}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuWitnessBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
+    /// Sets the salt of a circuit built with
+    /// `SudokuProblemTarget::add_solution_commitment`.
+    ///
+    /// The commitment itself is computed by the circuit.
+    pub fn set_solution_commitment_witness<F: RichField>(
+        witness: &mut PartialWitness<F>,
+        commitment_target: &SolutionCommitmentTarget,
+        salt: [F; NUM_HASH_OUT_ELTS],
+    ) {
+        witness.set_target_arr(&commitment_target.salt, &salt);
+    }
+
+    /// Computes, outside of the circuit, the commitment exposed by a circuit
+    /// built with `SudokuProblemTarget::add_solution_commitment`.
+    pub fn solution_commitment<F: RichField>(
+        solution: [[usize; SIZE]; SIZE],
+        salt: [F; NUM_HASH_OUT_ELTS],
+    ) -> HashOut<F> {
+        let mut commitment_inputs = Vec::with_capacity(SIZE * SIZE + NUM_HASH_OUT_ELTS);
+        commitment_inputs.extend(
+            solution
+                .iter()
+                .flatten()
+                .map(|&n| F::from_canonical_usize(n)),
+        );
+        commitment_inputs.extend(salt);
+
+        PoseidonHash::hash_no_pad(&commitment_inputs)
+    }
+}
@@ -1,71 +1,169 @@
-use crate::permutation::ApplyPermutation;
+use std::sync::Arc;
+
+use crate::{
+    permutation::ApplyPermutation,
+    utilities::connect_strategy::{ConnectStrategy, CopyConstraint},
+};
 
 use super::SudokuProblemTarget;
 
 use plonky2::{
-    field::extension::Extendable, hash::hash_types::RichField, plonk::circuit_builder::CircuitBuilder
+    field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+    plonk::circuit_builder::CircuitBuilder,
 };
 
-pub enum SudokuCircuitBuilder<const SIZE: usize, const SIZE_SQRT: usize> {}
+/// How `SudokuCircuitBuilder` proves that every row, column and region of
+/// the solution contains every symbol exactly once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SudokuConstraintStrategy {
+    /// Every row, column and region goes through a `PermutationGate` whose
+    /// outputs are connected to the symbols. The swap selectors of the gates
+    /// are part of the witness.
+    #[default]
+    PermutationGate,
+    /// A lookup table maps every cell `c` of the solution to `2^(c - 1)`,
+    /// and has no entries outside of `1 ..= SIZE`, so it range-checks the
+    /// cells too. Then, the multiset of the cells of a group is
+    /// `{1, ..., SIZE}` if and only if the sum of their images is
+    /// `2^SIZE - 1`: that sum has `SIZE` bits set, while a sum of `SIZE`
+    /// powers of two has less than `SIZE` bits set as soon as two of them
+    /// are equal.
+    ///
+    /// No swap selectors are needed. Since the entries of lookup tables are
+    /// `u16` values, only grids with `SIZE <= 16` are supported.
+    LookupTable,
+}
 
-impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuCircuitBuilder<SIZE, SIZE_SQRT> {
+pub enum SudokuCircuitBuilder<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize = BOX_ROWS,
+> {}
 
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuCircuitBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
     pub fn add_proof_of_sudoku_solution<const D: usize, F: RichField + Extendable<D>>(
         builder: &mut CircuitBuilder<F, D>,
-    ) -> Result<SudokuProblemTarget<SIZE, SIZE_SQRT>, ()> {
-        add_proof_of_sudoku_solution_helper::<false, SIZE, SIZE_SQRT, D, F>(builder)
+    ) -> Result<SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>, ()> {
+        Self::add_proof_of_sudoku_solution_with_strategy(
+            builder,
+            SudokuConstraintStrategy::default(),
+        )
+    }
+
+    /// Like `add_proof_of_sudoku_solution`, with the constraints on the
+    /// groups of cells of the solution chosen by `strategy`.
+    ///
+    /// Fails if `strategy` does not support grids of size `SIZE`.
+    pub fn add_proof_of_sudoku_solution_with_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        strategy: SudokuConstraintStrategy,
+    ) -> Result<SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>, ()> {
+        Self::add_proof_of_sudoku_solution_with_connect_strategy(builder, strategy, CopyConstraint)
     }
 
-    /// Like `add_proof_of_sudoku_solution`, but the circuit is slightly less
-    /// optimized, in a way that allows proof generation not to cause a panic
-    /// when an invalid witness is input into the circuit.
+    /// Like `add_proof_of_sudoku_solution_with_strategy`, with the equalities
+    /// enforced through `connect_strategy`.
     ///
-    /// Needed when we want to check that the circuit logic actually forbids
-    /// the generation of bogous proofs.
-    #[cfg(test)]
-    pub fn add_proof_of_sudoku_solution_fail_gracefully<const D: usize, F: RichField + Extendable<D>>(
+    /// With `TestEq`, the circuit is slightly less optimized, in a way that
+    /// allows proof generation not to cause a panic when an invalid witness
+    /// is input into the circuit. This is needed when we want to check that
+    /// the circuit logic actually forbids the generation of bogus proofs.
+    /// With `SudokuConstraintStrategy::LookupTable`, proof generation still
+    /// panics if a cell of the solution is not a symbol.
+    pub fn add_proof_of_sudoku_solution_with_connect_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
         builder: &mut CircuitBuilder<F, D>,
-    ) -> Result<SudokuProblemTarget<SIZE, SIZE_SQRT>, ()> {
-        add_proof_of_sudoku_solution_helper::<true, SIZE, SIZE_SQRT, D, F>(builder)
+        strategy: SudokuConstraintStrategy,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>, ()> {
+        let symbols = sudoku_symbols(builder);
+        add_proof_of_sudoku_solution_helper(builder, symbols, strategy, connect_strategy)
+    }
+
+    /// Adds the proofs of the solutions of `N` Sudoku puzzles to the circuit.
+    ///
+    /// The puzzles are checked independently of each other, as if
+    /// `add_proof_of_sudoku_solution` was called `N` times, so the circuit
+    /// is only valid if every solution is.
+    pub fn add_proof_of_sudoku_solutions<
+        const N: usize,
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<[SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>; N], ()> {
+        Self::add_proof_of_sudoku_solutions_with_connect_strategy(builder, CopyConstraint)
+    }
+
+    /// Like `add_proof_of_sudoku_solutions`, with the equalities of every
+    /// puzzle enforced through `connect_strategy`.
+    /// See `add_proof_of_sudoku_solution_with_connect_strategy`.
+    pub fn add_proof_of_sudoku_solutions_with_connect_strategy<
+        const N: usize,
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<[SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>; N], ()> {
+        let symbols = sudoku_symbols(builder);
+        let mut targets = Vec::with_capacity(N);
+        for _ in 0..N {
+            targets.push(add_proof_of_sudoku_solution_helper(
+                builder,
+                symbols,
+                SudokuConstraintStrategy::default(),
+                connect_strategy,
+            )?);
+        }
+        match targets.try_into() {
+            Ok(targets) => Ok(targets),
+            Err(_) => unreachable!("we pushed exactly `N` targets"),
+        }
     }
 }
 
+/// Symbols are `1 ..= SIZE`. `0` is reserved to the values in the
+/// problem grid, where it means "the cell is empty".
+fn sudoku_symbols<const SIZE: usize, const D: usize, F: RichField + Extendable<D>>(
+    builder: &mut CircuitBuilder<F, D>,
+) -> [Target; SIZE] {
+    core::array::from_fn(|idx| builder.constant(F::from_canonical_usize(idx + 1)))
+}
+
 fn add_proof_of_sudoku_solution_helper<
-    const TEST_MODE: bool,
     const SIZE: usize,
-    const SIZE_SQRT: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize,
     const D: usize,
     F: RichField + Extendable<D>,
 >(
     builder: &mut CircuitBuilder<F, D>,
-) -> Result<SudokuProblemTarget<SIZE, SIZE_SQRT>, ()> {
+    symbols: [Target; SIZE],
+    strategy: SudokuConstraintStrategy,
+    connect_strategy: impl ConnectStrategy,
+) -> Result<SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>, ()> {
     // Rust's constant computations don't allow to perform operations
-    // on generic constants yet, so to get the square root of SIZE we
-    // are stuck with this ugliness.
-    assert_eq!(SIZE_SQRT * SIZE_SQRT, SIZE);
-
-    // If TEST_MODE is true, we will use
-    // crate::utilities::test_connect_gate::TestEq::connect
-    // instead of the usual
-    // CircuitBuilder::connect
-    // to make two cells equal in a circuit.
-    // This is done to avoid panics if the witness is invalid.
-    //
-    // The standard config has 80 routable wires, and the
-    // TestEq gate needs 2 wires per operation.
-    const N_TEST_EQ_OPS: usize = 80 / 2;
-
+    // on generic constants yet, so to check that the regions have the
+    // right size we are stuck with this ugliness.
+    assert_eq!(BOX_ROWS * BOX_COLS, SIZE);
 
-    let schedule_length = builder.permutation_swap_schedule_length(SIZE);
+    let schedule_length = match strategy {
+        SudokuConstraintStrategy::PermutationGate => builder.permutation_swap_schedule_length(SIZE),
+        SudokuConstraintStrategy::LookupTable => 0,
+    };
 
     let out = SudokuProblemTarget {
         problem: core::array::from_fn(|_| builder.add_virtual_target_arr()),
         solution: core::array::from_fn(|_| builder.add_virtual_target_arr()),
-        symbols: 
-            // Symbols are `1 ..= SIZE`. `0` is reserved to the values in the
-            // problem grid, where it means "the cell is empty".
-            core::array::from_fn(|idx| builder.constant(F::from_canonical_usize(idx + 1)))
-        ,
+        symbols,
         row_swap_selectors: core::array::from_fn(|_| builder.add_virtual_targets(schedule_length)),
         column_swap_selectors: core::array::from_fn(|_| {
             builder.add_virtual_targets(schedule_length)
@@ -75,41 +173,15 @@ fn add_proof_of_sudoku_solution_helper<
         }),
     };
 
-    for (group, selectors) in
-        // Applying row constraints to the solution
-        SudokuProblemTarget::get_rows(&out.solution)
-            .iter()
-            .zip(out.row_swap_selectors.iter())
-        .chain(
-            // Applying column constraints to the solution
-            SudokuProblemTarget::get_columns(&out.solution)
-            .iter()
-            .zip(out.column_swap_selectors.iter())
-        ).chain(
-            // Applying region constraints to the solution
-            SudokuProblemTarget::<SIZE, SIZE_SQRT>::get_regions(&out.solution)
-            .iter()
-            .zip(out.region_swap_selectors.iter())
-        )
-    {
-        let out_targets = builder.add_virtual_target_arr::<SIZE>();
-        builder.add_permutation_gate(group, selectors, &out_targets, true)?;
-        
-        // if we are in test mode, we will add test-eq constraints.
-        // those constraints are logically equivalent to `CircuitBuilder::connect`
-        // but they don't cause a panic with an invalid witness.
-        
-            for (lhs, rhs) in out_targets.into_iter().zip(out.symbols) {
-                if TEST_MODE {
-                    crate::utilities::test_connect_gate::TestEq::<N_TEST_EQ_OPS>::connect(builder, lhs, rhs);
-                
-            } else {
-                builder.connect(lhs, rhs);
-            }
+    match strategy {
+        SudokuConstraintStrategy::PermutationGate => {
+            add_permutation_constraints(builder, &out, connect_strategy)?
+        }
+        SudokuConstraintStrategy::LookupTable => {
+            add_lookup_constraints(builder, &out, connect_strategy)?
         }
     }
 
-    let zero_target = builder.zero();
     // We enforce the constraint that, for all i < SIZE and all j < SIZE,
     //  if `problem[i][j] != 0`, then `problem[i][j] == solution[i][j]`.
     for (problem_row, solution_row) in out.problem.into_iter().zip(out.solution) {
@@ -118,15 +190,92 @@ fn add_proof_of_sudoku_solution_helper<
             // `problem_cell * (problem_cell - solution_cell) == 0`
             let delta = builder.sub(problem_cell, solution_cell);
             let constraint = builder.mul(problem_cell, delta);
-
-            // If we are in test mode, we use TestEq gate to avoid panics.
-            if TEST_MODE {
-                crate::utilities::test_connect_gate::TestEq::<N_TEST_EQ_OPS>::connect(builder, constraint, zero_target);
-            } else {
-                builder.assert_zero(constraint);
-            }
+            connect_strategy.assert_zero(builder, constraint);
         }
     }
 
     Ok(out)
-}
\ No newline at end of file
+}
+
+fn add_permutation_constraints<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize,
+    const D: usize,
+    F: RichField + Extendable<D>,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    out: &SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+    connect_strategy: impl ConnectStrategy,
+) -> Result<(), ()> {
+    for (group, selectors) in
+        // Applying row constraints to the solution
+        SudokuProblemTarget::get_rows(&out.solution)
+            .iter()
+            .zip(out.row_swap_selectors.iter())
+            .chain(
+                // Applying column constraints to the solution
+                SudokuProblemTarget::get_columns(&out.solution)
+                    .iter()
+                    .zip(out.column_swap_selectors.iter()),
+            )
+            .chain(
+                // Applying region constraints to the solution
+                SudokuProblemTarget::<SIZE, BOX_ROWS, BOX_COLS>::get_regions(&out.solution)
+                    .iter()
+                    .zip(out.region_swap_selectors.iter()),
+            )
+    {
+        let out_targets = builder.add_virtual_target_arr::<SIZE>();
+        builder.add_permutation_gate(group, selectors, &out_targets, true)?;
+
+        for (lhs, rhs) in out_targets.into_iter().zip(out.symbols) {
+            connect_strategy.connect(builder, lhs, rhs);
+        }
+    }
+
+    Ok(())
+}
+
+fn add_lookup_constraints<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize,
+    const D: usize,
+    F: RichField + Extendable<D>,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    out: &SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
+    connect_strategy: impl ConnectStrategy,
+) -> Result<(), ()> {
+    if SIZE > 16 {
+        return Err(());
+    }
+
+    // We look up `cell - 1` rather than `cell`, because the lookup
+    // generator of plonky2 expects the `i`-th entry of the table
+    // to have input `i`.
+    let lut_index = builder.add_lookup_table_from_pairs(Arc::new(Vec::from_iter(
+        (0..SIZE as u16).map(|input| (input, 1 << input)),
+    )));
+    let encoded_solution = out.solution.map(|row| {
+        row.map(|cell| {
+            let input = builder.add_const(cell, F::NEG_ONE);
+            builder.add_lookup_from_index(input, lut_index)
+        })
+    });
+
+    let expected_sum = builder.constant(F::from_canonical_u64((1 << SIZE) - 1));
+    for group in SudokuProblemTarget::get_rows(&encoded_solution)
+        .iter()
+        .chain(SudokuProblemTarget::get_columns(&encoded_solution).iter())
+        .chain(
+            SudokuProblemTarget::<SIZE, BOX_ROWS, BOX_COLS>::get_regions(&encoded_solution).iter(),
+        )
+    {
+        let sum = builder.add_many(group);
+        connect_strategy.connect(builder, sum, expected_sum);
+    }
+
+    Ok(())
+}
@@ -9,14 +9,20 @@ use crate::permutation::{DefaultSwapSchedule, SwapSchedule};
 
 use super::SudokuProblemTarget;
 
-pub enum SudokuWitnessBuilder<const SIZE: usize, const SIZE_SQRT: usize> {}
-
-impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_SQRT> {
+pub enum SudokuWitnessBuilder<
+    const SIZE: usize,
+    const BOX_ROWS: usize,
+    const BOX_COLS: usize = BOX_ROWS,
+> {}
+
+impl<const SIZE: usize, const BOX_ROWS: usize, const BOX_COLS: usize>
+    SudokuWitnessBuilder<SIZE, BOX_ROWS, BOX_COLS>
+{
     /// This function sets the problem grids for a sudoku and then fills the
     /// advice values for the permutation gates associated to the sudoku cirucit.
     pub fn set_sudoku_witness<F: Field>(
         witness: &mut PartialWitness<F>,
-        sudoku_target: &SudokuProblemTarget<SIZE, SIZE_SQRT>,
+        sudoku_target: &SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
         problem: [[usize; SIZE]; SIZE],
         solution: [[usize; SIZE]; SIZE],
     ) {
@@ -40,25 +46,41 @@ impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_
         Self::compute_swap_selectors(witness, sudoku_target).expect("compute swap selectors fails if the problem and solution grids have not been set. We just set them.");
     }
 
+    /// Like `set_sudoku_witness`, for the `N` puzzles of a circuit built
+    /// with `SudokuCircuitBuilder::add_proof_of_sudoku_solutions`.
+    /// The `i`-th puzzle is made of `problems[i]` and `solutions[i]`.
+    pub fn set_sudoku_batch_witness<F: Field, const N: usize>(
+        witness: &mut PartialWitness<F>,
+        sudoku_targets: &[SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>; N],
+        problems: [[[usize; SIZE]; SIZE]; N],
+        solutions: [[[usize; SIZE]; SIZE]; N],
+    ) {
+        for ((sudoku_target, problem), solution) in
+            sudoku_targets.iter().zip(problems).zip(solutions)
+        {
+            Self::set_sudoku_witness(witness, sudoku_target, problem, solution);
+        }
+    }
+
     /// This function sets the advice values for the permutation gates
     /// associated to a sudoku circuit.
     /// The function assumes that the problem and solution grids have been
     /// set beforehand, and fails otherwise.
     pub fn compute_swap_selectors<F: Field>(
         witness: &mut PartialWitness<F>,
-        sudoku_target: &SudokuProblemTarget<SIZE, SIZE_SQRT>,
+        sudoku_target: &SudokuProblemTarget<SIZE, BOX_ROWS, BOX_COLS>,
     ) -> Result<(), ()> {
         let symbols_to_usize: HashMap<F, usize, std::collections::hash_map::RandomState> =
             HashMap::from_iter((0..SIZE).map(|n| (F::from_canonical_usize(n + 1), n)));
 
         let mut solution = [[0; SIZE]; SIZE];
 
-        for row_idx in 0..SIZE {
-            for col_idx in 0..SIZE {
-                solution[row_idx][col_idx] = *symbols_to_usize
+        for (solution_row, target_row) in solution.iter_mut().zip(sudoku_target.solution.iter()) {
+            for (solution_cell, target_cell) in solution_row.iter_mut().zip(target_row) {
+                *solution_cell = *symbols_to_usize
                     .get(
                         &witness
-                            .try_get_target(sudoku_target.solution[row_idx][col_idx])
+                            .try_get_target(*target_cell)
                             // None if the target has not been set in the witness yet.
                             .ok_or(())?,
                     )
@@ -90,7 +112,7 @@ impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_
             .zip(SudokuProblemTarget::get_rows(&solution))
         {
             for (target, selector_value) in selector_targets
-                .into_iter()
+                .iter()
                 .zip(compute_swap_selectors(&mut row))
             {
                 witness.set_target(*target, selector_value);
@@ -105,7 +127,7 @@ impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_
             .zip(SudokuProblemTarget::get_columns(&solution))
         {
             for (target, selector_value) in selector_targets
-                .into_iter()
+                .iter()
                 .zip(compute_swap_selectors(&mut column))
             {
                 witness.set_target(*target, selector_value);
@@ -114,11 +136,13 @@ impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_
 
         // Computing swap selectors for permutations on regions.
         // Analogous to what we did on rows and columns.
-        for (selector_targets, mut region) in sudoku_target.region_swap_selectors.iter().zip(
-            SudokuProblemTarget::<SIZE, SIZE_SQRT>::get_regions(&solution),
-        ) {
+        for (selector_targets, mut region) in sudoku_target
+            .region_swap_selectors
+            .iter()
+            .zip(SudokuProblemTarget::<SIZE, BOX_ROWS, BOX_COLS>::get_regions(&solution))
+        {
             for (target, selector_value) in selector_targets
-                .into_iter()
+                .iter()
                 .zip(compute_swap_selectors(&mut region))
             {
                 witness.set_target(*target, selector_value);
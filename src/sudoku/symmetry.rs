@@ -0,0 +1,306 @@
+use plonky2::{
+    field::{extension::Extendable, types::Field},
+    hash::hash_types::RichField,
+    iop::{
+        target::{BoolTarget, Target},
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+use super::{SudokuCircuitBuilder, SudokuProblemTarget, SudokuWitnessBuilder};
+use crate::{
+    permutation::{inverse_permutation, ApplyPermutation, DefaultSwapSchedule, SwapSchedule},
+    utilities::{
+        connect_strategy::{ConnectStrategy, CopyConstraint},
+        random_access::large_random_access,
+    },
+};
+
+/// A transformation of Sudoku grids that maps valid puzzles to valid puzzles.
+///
+/// Calling
+///
+/// ``` text
+/// rows(i) = bands[i / SIZE_SQRT] * SIZE_SQRT + rows_in_bands[i / SIZE_SQRT][i % SIZE_SQRT]
+/// ```
+///
+/// and `columns(j)` the same with `stacks` and `columns_in_stacks`,
+/// the transformation maps `grid` to
+///
+/// ``` text
+/// output[i][j] = relabel(transposed_grid[rows(i)][columns(j)])
+/// ```
+///
+/// where `transposed_grid` is the transpose of `grid` if `transpose` is set,
+/// `relabel(0) = 0`, and `relabel(d) = relabeling[d - 1]` for the other symbols.
+///
+/// Every array must be a permutation, of `0..SIZE_SQRT` or of `1..=SIZE`
+/// for `relabeling`.
+///
+/// Only grids with square regions are supported, since transposing a grid
+/// with rectangular regions does not output a valid grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SudokuSymmetry<const SIZE: usize, const SIZE_SQRT: usize> {
+    pub transpose: bool,
+    pub bands: [usize; SIZE_SQRT],
+    pub rows_in_bands: [[usize; SIZE_SQRT]; SIZE_SQRT],
+    pub stacks: [usize; SIZE_SQRT],
+    pub columns_in_stacks: [[usize; SIZE_SQRT]; SIZE_SQRT],
+    pub relabeling: [usize; SIZE],
+}
+
+impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuSymmetry<SIZE, SIZE_SQRT> {
+    pub fn identity() -> Self {
+        Self {
+            transpose: false,
+            bands: core::array::from_fn(|idx| idx),
+            rows_in_bands: [core::array::from_fn(|idx| idx); SIZE_SQRT],
+            stacks: core::array::from_fn(|idx| idx),
+            columns_in_stacks: [core::array::from_fn(|idx| idx); SIZE_SQRT],
+            relabeling: core::array::from_fn(|idx| idx + 1),
+        }
+    }
+
+    /// Applies the transformation to a grid, where empty cells are `0`.
+    pub fn apply(&self, grid: [[usize; SIZE]; SIZE]) -> [[usize; SIZE]; SIZE] {
+        let grid = if self.transpose {
+            SudokuProblemTarget::<SIZE, 0>::get_columns(&grid)
+        } else {
+            grid
+        };
+        let rows = Self::line_mapping(&self.bands, &self.rows_in_bands);
+        let columns = Self::line_mapping(&self.stacks, &self.columns_in_stacks);
+
+        core::array::from_fn(|i| {
+            core::array::from_fn(|j| match grid[rows[i]][columns[j]] {
+                0 => 0,
+                symbol => self.relabeling[symbol - 1],
+            })
+        })
+    }
+
+    // `mapping[i]` is the line of the input grid that ends up in the `i`-th line.
+    fn line_mapping(
+        blocks: &[usize; SIZE_SQRT],
+        lines_in_blocks: &[[usize; SIZE_SQRT]; SIZE_SQRT],
+    ) -> [usize; SIZE] {
+        core::array::from_fn(|i| {
+            blocks[i / SIZE_SQRT] * SIZE_SQRT + lines_in_blocks[i / SIZE_SQRT][i % SIZE_SQRT]
+        })
+    }
+}
+
+/// The targets added by `SudokuCircuitBuilder::add_proof_of_sudoku_equivalence`.
+///
+/// `grid_a` and `grid_b` are public inputs, in this order, row by row,
+/// while the transformation that maps one to the other stays private.
+pub struct SudokuEquivalenceTarget<const SIZE: usize, const SIZE_SQRT: usize> {
+    grid_a: [[Target; SIZE]; SIZE],
+    grid_b: [[Target; SIZE]; SIZE],
+    transpose: BoolTarget,
+    band_selectors: Vec<Target>,
+    row_selectors: [Vec<Target>; SIZE_SQRT],
+    stack_selectors: Vec<Target>,
+    column_selectors: [Vec<Target>; SIZE_SQRT],
+    relabeling_selectors: Vec<Target>,
+}
+
+impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuCircuitBuilder<SIZE, SIZE_SQRT> {
+    /// Proves that the public grid `grid_b` is obtained from the public grid
+    /// `grid_a` through a private `SudokuSymmetry`.
+    ///
+    /// The rows (and columns) are rearranged in two steps: a permutation
+    /// gate moves the bands around, then each band has its own permutation
+    /// gate for the rows inside it. This way, only the permutations that map
+    /// Sudoku puzzles to Sudoku puzzles can be proven.
+    ///
+    /// The symbols are relabeled by looking up every cell in a table that
+    /// maps `0` to itself, and `1..=SIZE` to a private permutation of them.
+    /// The cells of the grids must be in `0..=SIZE`. The circuit does not
+    /// check it, since the verifier knows the grids anyway.
+    pub fn add_proof_of_sudoku_equivalence<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Result<SudokuEquivalenceTarget<SIZE, SIZE_SQRT>, ()> {
+        Self::add_proof_of_sudoku_equivalence_with_connect_strategy(builder, CopyConstraint)
+    }
+
+    /// Like `add_proof_of_sudoku_equivalence`, but the relabeled cells are
+    /// enforced to be the cells of `grid_b` through `connect_strategy`.
+    pub fn add_proof_of_sudoku_equivalence_with_connect_strategy<
+        const D: usize,
+        F: RichField + Extendable<D>,
+    >(
+        builder: &mut CircuitBuilder<F, D>,
+        connect_strategy: impl ConnectStrategy,
+    ) -> Result<SudokuEquivalenceTarget<SIZE, SIZE_SQRT>, ()> {
+        assert_eq!(SIZE_SQRT * SIZE_SQRT, SIZE);
+
+        let block_schedule_length = builder.permutation_swap_schedule_length(SIZE_SQRT);
+        let symbol_schedule_length = builder.permutation_swap_schedule_length(SIZE);
+
+        let out = SudokuEquivalenceTarget {
+            grid_a: core::array::from_fn(|_| builder.add_virtual_public_input_arr()),
+            grid_b: core::array::from_fn(|_| builder.add_virtual_public_input_arr()),
+            transpose: builder.add_virtual_bool_target_safe(),
+            band_selectors: builder.add_virtual_targets(block_schedule_length),
+            row_selectors: core::array::from_fn(|_| {
+                builder.add_virtual_targets(block_schedule_length)
+            }),
+            stack_selectors: builder.add_virtual_targets(block_schedule_length),
+            column_selectors: core::array::from_fn(|_| {
+                builder.add_virtual_targets(block_schedule_length)
+            }),
+            relabeling_selectors: builder.add_virtual_targets(symbol_schedule_length),
+        };
+
+        let transposed_a = SudokuProblemTarget::<SIZE, 0>::get_columns(&out.grid_a);
+        let grid: [[Target; SIZE]; SIZE] = core::array::from_fn(|i| {
+            core::array::from_fn(|j| {
+                builder.select(out.transpose, transposed_a[i][j], out.grid_a[i][j])
+            })
+        });
+
+        // We permute the columns as if they were the rows of the transposed grid.
+        let grid = SudokuProblemTarget::<SIZE, 0>::get_columns(&Self::permute_rows(
+            builder,
+            SudokuProblemTarget::<SIZE, 0>::get_columns(&grid),
+            &out.stack_selectors,
+            &out.column_selectors,
+        )?);
+        let grid = Self::permute_rows(builder, grid, &out.band_selectors, &out.row_selectors)?;
+
+        // `relabeling_table[d]` is the symbol that replaces `d`.
+        let mut relabeling_table = vec![builder.zero()];
+        for idx in builder.permutation_of_indices(SIZE, &out.relabeling_selectors, true)? {
+            relabeling_table.push(builder.add_const(idx, F::ONE));
+        }
+
+        for (row, row_b) in grid.iter().zip(out.grid_b.iter()) {
+            for (&cell, &cell_b) in row.iter().zip(row_b) {
+                let relabeled_cell = large_random_access(builder, cell, relabeling_table.clone());
+                connect_strategy.connect(builder, relabeled_cell, cell_b);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Moves the `bands`, i.e. the groups of `SIZE_SQRT` consecutive rows,
+    /// around with `band_selectors`, then permutes the rows inside the
+    /// `b`-th band with `row_selectors[b]`.
+    fn permute_rows<const D: usize, F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+        grid: [[Target; SIZE]; SIZE],
+        band_selectors: &[Target],
+        row_selectors: &[Vec<Target>; SIZE_SQRT],
+    ) -> Result<[[Target; SIZE]; SIZE], ()> {
+        let mut columns = SudokuProblemTarget::<SIZE, 0>::get_columns(&grid);
+
+        for (col_idx, column) in columns.iter_mut().enumerate() {
+            // The selectors are the same for every column,
+            // so it is enough to check that they are boolean once.
+            let enforce_boolean_selectors = col_idx == 0;
+
+            for offset in 0..SIZE_SQRT {
+                let inputs =
+                    Vec::from_iter((0..SIZE_SQRT).map(|band| column[band * SIZE_SQRT + offset]));
+                let outputs = builder.add_virtual_targets(SIZE_SQRT);
+                builder.add_permutation_gate(
+                    &inputs,
+                    band_selectors,
+                    &outputs,
+                    enforce_boolean_selectors,
+                )?;
+                for (band, output) in outputs.into_iter().enumerate() {
+                    column[band * SIZE_SQRT + offset] = output;
+                }
+            }
+
+            for (band, selectors) in column.chunks_mut(SIZE_SQRT).zip(row_selectors) {
+                let outputs = builder.add_virtual_targets(SIZE_SQRT);
+                builder.add_permutation_gate(
+                    band,
+                    selectors,
+                    &outputs,
+                    enforce_boolean_selectors,
+                )?;
+                band.copy_from_slice(&outputs);
+            }
+        }
+
+        Ok(SudokuProblemTarget::<SIZE, 0>::get_columns(&columns))
+    }
+}
+
+impl<const SIZE: usize, const SIZE_SQRT: usize> SudokuWitnessBuilder<SIZE, SIZE_SQRT> {
+    /// Sets the grids of a circuit built with
+    /// `SudokuCircuitBuilder::add_proof_of_sudoku_equivalence`, and the
+    /// advice values that encode `symmetry`.
+    ///
+    /// If `grid_b` is not `symmetry.apply(grid_a)`, the witness is set anyway,
+    /// but the proof generation fails.
+    pub fn set_sudoku_equivalence_witness<F: Field>(
+        witness: &mut PartialWitness<F>,
+        equivalence_target: &SudokuEquivalenceTarget<SIZE, SIZE_SQRT>,
+        grid_a: [[usize; SIZE]; SIZE],
+        grid_b: [[usize; SIZE]; SIZE],
+        symmetry: SudokuSymmetry<SIZE, SIZE_SQRT>,
+    ) {
+        for (targets, grid) in [
+            (&equivalence_target.grid_a, grid_a),
+            (&equivalence_target.grid_b, grid_b),
+        ] {
+            for (row_targets, row_values) in targets.iter().zip(grid) {
+                witness.set_target_arr(row_targets, &row_values.map(F::from_canonical_usize));
+            }
+        }
+
+        witness.set_bool_target(equivalence_target.transpose, symmetry.transpose);
+
+        // The permutation gates output `items[mapping[i]]` in the `i`-th
+        // position when the swaps are selected for the inverse of `mapping`.
+        fn set_selectors<F: Field>(
+            witness: &mut PartialWitness<F>,
+            targets: &[Target],
+            mapping: &[usize],
+        ) {
+            let mut inverse_mapping = vec![0; mapping.len()];
+            inverse_permutation(mapping, &mut inverse_mapping);
+            for (target, (selector, _idx1, _idx2)) in
+                targets
+                    .iter()
+                    .zip(DefaultSwapSchedule::permutation_to_swap_schedule(
+                        &mut inverse_mapping,
+                    ))
+            {
+                witness.set_target(*target, F::from_bool(selector));
+            }
+        }
+
+        set_selectors(witness, &equivalence_target.band_selectors, &symmetry.bands);
+        set_selectors(
+            witness,
+            &equivalence_target.stack_selectors,
+            &symmetry.stacks,
+        );
+        for (targets, mapping) in equivalence_target
+            .row_selectors
+            .iter()
+            .zip(symmetry.rows_in_bands.iter())
+            .chain(
+                equivalence_target
+                    .column_selectors
+                    .iter()
+                    .zip(symmetry.columns_in_stacks.iter()),
+            )
+        {
+            set_selectors(witness, targets, mapping);
+        }
+        set_selectors(
+            witness,
+            &equivalence_target.relabeling_selectors,
+            &symmetry.relabeling.map(|symbol| symbol - 1),
+        );
+    }
+}
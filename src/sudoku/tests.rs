@@ -1,5 +1,16 @@
-use plonky2::{field::types::Field, iop::witness::WitnessWrite};
-use rand::distributions::Distribution;
+use plonky2::{
+    field::types::{Field, PrimeField64},
+    iop::witness::WitnessWrite,
+};
+use rand::{distributions::Distribution, Rng};
+
+use super::SudokuConstraintStrategy;
+use crate::utilities::metrics::{global_metrics, prove_with_metrics, verify_with_metrics, Phase};
+use crate::utilities::{
+    fixtures::{fixture_path, Fixture},
+    seeded_rng::test_rng,
+    test_connect_gate::StandardTestEq,
+};
 
 /// Helper function to generate symbols and a list of problems
 /// The return value is a tuple, laid out as
@@ -7,8 +18,12 @@ use rand::distributions::Distribution;
 ///
 /// The values provided are usize arrays. To use them in a
 /// sudoku circuit, they have to be converted in Fp values.
+///
+/// The masks that hide the cells of the solutions are drawn from `rng`.
+#[allow(clippy::type_complexity)]
 fn numeric_setup_values(
     nr_random_masks_per_problem: usize,
+    rng: &mut impl Rng,
 ) -> (
     [usize; 9],
     impl IntoIterator<Item = ([[usize; 9]; 9], [[usize; 9]; 9])>,
@@ -63,25 +78,26 @@ fn numeric_setup_values(
     ];
 
     let nr_grids = grids.len();
+    // We draw the masks beforehand, so that the returned iterator
+    // does not borrow `rng`.
+    let masks = Vec::from_iter(
+        (0..nr_grids * nr_random_masks_per_problem).map(|_| rng.gen::<[[bool; 9]; 9]>()),
+    );
     // We transform the sudoku grids into an iterator of grids of field elements
-    let grids_iter = (0..nr_grids * nr_random_masks_per_problem)
-        .into_iter()
-        .map(|_| rand::random::<[[bool; 9]; 9]>())
-        .enumerate()
-        .map(move |(idx, mask)| {
-            let grid = grids[idx / nr_random_masks_per_problem];
-
-            let masked_grid = core::array::from_fn(|col_idx| {
-                core::array::from_fn(|row_idx| {
-                    if mask[col_idx][row_idx] {
-                        0
-                    } else {
-                        grid[col_idx][row_idx]
-                    }
-                })
-            });
-            (grid, masked_grid)
+    let grids_iter = masks.into_iter().enumerate().map(move |(idx, mask)| {
+        let grid = grids[idx / nr_random_masks_per_problem];
+
+        let masked_grid = core::array::from_fn(|col_idx| {
+            core::array::from_fn(|row_idx| {
+                if mask[col_idx][row_idx] {
+                    0
+                } else {
+                    grid[col_idx][row_idx]
+                }
+            })
         });
+        (grid, masked_grid)
+    });
 
     (symbols, grids_iter)
 }
@@ -91,6 +107,7 @@ fn numeric_setup_values(
 #[test]
 fn test_valid_sudoku_problems() {
     const NR_RANDOM_MASKS_PER_PROBLEM: usize = 4;
+    const WORKFLOW: &str = "sudoku 9x9";
 
     const SIZE: usize = 9;
     const SIZE_SQRT: usize = 3;
@@ -100,6 +117,8 @@ fn test_valid_sudoku_problems() {
     type BaseField =
         <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
 
+    global_metrics().enable();
+
     let circuit_config =
         plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_zk_config();
 
@@ -112,9 +131,14 @@ fn test_valid_sudoku_problems() {
         super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(&mut builder)
             .expect("Circuit building goes wrong.");
 
-    let circuit = builder.build::<PlonkConfig>();
+    let circuit = crate::time_it! {
+        builder.build::<PlonkConfig>();
+        Phase::CircuitBuild, WORKFLOW
+    };
 
-    let (_symbols, sudoku_problem_instances) = numeric_setup_values(NR_RANDOM_MASKS_PER_PROBLEM);
+    let mut rng = test_rng();
+    let (_symbols, sudoku_problem_instances) =
+        numeric_setup_values(NR_RANDOM_MASKS_PER_PROBLEM, &mut rng);
 
     for (solution, problem) in sudoku_problem_instances {
         let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
@@ -126,22 +150,14 @@ fn test_valid_sudoku_problems() {
             solution,
         );
 
-        let proof = crate::time_it! {
-            circuit.prove(witness).expect("proof generation goes wrong");
-            "Proof generation takes {:?}"
-        };
+        let proof =
+            prove_with_metrics(&circuit, witness, WORKFLOW).expect("proof generation goes wrong");
 
-        println! {
-            "Proof size is {}",
-            proof.to_bytes().len()
-        };
+        verify_with_metrics(&circuit, proof, WORKFLOW).expect("Proof verification goes wrong");
+    }
 
-        crate::time_it! {
-            circuit
-                .verify(proof)
-                .expect("Proof verification goes wrong");
-            "Proof verification takes {:?}"
-        }
+    for metric in global_metrics().workflow_metrics(WORKFLOW) {
+        println!("{metric}");
     }
 }
 
@@ -172,7 +188,9 @@ fn test_invalid_sudoku_problems() {
     let uniform_size = rand::distributions::Uniform::new(0, SIZE);
     let uniform_size_minus_1 = rand::distributions::Uniform::new(1, SIZE);
 
-    let (_symbols, sudoku_problem_instances) = numeric_setup_values(NR_RANDOM_MASKS_PER_PROBLEM);
+    let mut rng = test_rng();
+    let (_symbols, sudoku_problem_instances) =
+        numeric_setup_values(NR_RANDOM_MASKS_PER_PROBLEM, &mut rng);
 
     for (mut solution, problem) in sudoku_problem_instances {
         // We have to build the circuit from scratch at every iteration because
@@ -184,8 +202,10 @@ fn test_invalid_sudoku_problems() {
         >::new(circuit_config.clone());
 
         let sudoku_problem_target =
-            super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_fail_gracefully(
+            super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_connect_strategy(
                 &mut builder,
+                SudokuConstraintStrategy::PermutationGate,
+                StandardTestEq::default(),
             )
             .expect("Circuit building goes wrong.");
 
@@ -195,13 +215,13 @@ fn test_invalid_sudoku_problems() {
         // changing a random cell to a random invalid value.
 
         // We extract the coordinates of the cell to corrupt.
-        let err_row_idx = uniform_size.sample(&mut rand::rngs::OsRng);
-        let err_col_idx = uniform_size.sample(&mut rand::rngs::OsRng);
+        let err_row_idx = uniform_size.sample(&mut rng);
+        let err_col_idx = uniform_size.sample(&mut rng);
 
         // We change its value to any other valid symbol.
         // We assume that `_symbols = [1..=SIZE]`
         let old_value = solution[err_row_idx][err_col_idx];
-        let new_value = uniform_size_minus_1.sample(&mut rand::rngs::OsRng);
+        let new_value = uniform_size_minus_1.sample(&mut rng);
         let new_value = if new_value < old_value {
             new_value
         } else {
@@ -240,3 +260,1015 @@ fn test_invalid_sudoku_problems() {
             .expect_err("An invalid proof was accepted");
     }
 }
+
+/// Tests that we are able to aggregate many sudoku proofs into one,
+/// and that the aggregated proof commits to the list of puzzles.
+#[test]
+fn test_sudoku_aggregation() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PlonkConfig as plonky2::plonk::config::GenericConfig<2>>::F;
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problems = [
+        [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]],
+        [[0, 2, 0, 0], [0, 0, 0, 2], [2, 0, 0, 0], [0, 0, 2, 0]],
+        [[0, 0, 0, 0], [3, 0, 0, 0], [0, 0, 0, 3], [0, 3, 0, 0]],
+    ];
+
+    let aggregator = super::SudokuAggregator::<SIZE, SIZE_SQRT>::new(
+        plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+    )
+    .expect("Circuit building goes wrong.");
+
+    let proofs = Vec::from_iter(problems.iter().map(|problem| {
+        aggregator
+            .prove_solution(*problem, solution)
+            .expect("proof generation goes wrong")
+    }));
+
+    let aggregated_proof = aggregator
+        .aggregate(proofs.clone())
+        .expect("proof aggregation goes wrong");
+
+    // 3 proofs need a tree of height 2.
+    assert_eq!(aggregated_proof.height, 2);
+    assert_eq!(aggregated_proof.n_puzzles(), 3);
+    assert_eq!(
+        aggregated_proof.commitment(),
+        super::SudokuAggregator::<SIZE, SIZE_SQRT>::puzzles_commitment(&problems)
+    );
+    assert_ne!(
+        aggregated_proof.commitment(),
+        super::SudokuAggregator::<SIZE, SIZE_SQRT>::puzzles_commitment(&problems[..2])
+    );
+
+    aggregator
+        .verify(aggregated_proof)
+        .expect("Verification of aggregated proof fails.");
+
+    // The padding of the odd levels does not make `[A]` and `[A, A]`
+    // indistinguishable.
+    let [single, repeated] = [1, 2].map(|n_copies| {
+        let aggregated_proof = aggregator
+            .aggregate(vec![proofs[0].clone(); n_copies])
+            .expect("proof aggregation goes wrong");
+        assert_eq!(aggregated_proof.n_puzzles(), n_copies as u64);
+        assert_eq!(
+            aggregated_proof.commitment(),
+            super::SudokuAggregator::<SIZE, SIZE_SQRT>::puzzles_commitment(&vec![
+                problems[0];
+                n_copies
+            ])
+        );
+        aggregated_proof
+    });
+    assert_ne!(single.commitment(), repeated.commitment());
+
+    // The height must match the number of puzzles.
+    let mut wrong_height = single;
+    wrong_height.height = 2;
+    assert!(aggregator.verify(wrong_height).is_err());
+
+    // A proof with tampered public inputs must be rejected
+    // before the aggregation starts.
+    let mut tampered_proofs = proofs;
+    tampered_proofs[1].public_inputs[0] = BaseField::ONE;
+    assert!(
+        aggregator.aggregate(tampered_proofs).is_err(),
+        "an invalid proof is aggregated"
+    );
+}
+
+/// Tests that a proof bound to a prover identity and a challenge nonce
+/// is not accepted under a different identity or nonce.
+#[test]
+fn test_sudoku_prover_binding() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_zk_config(),
+        );
+
+    let sudoku_problem_target =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    let binding_target = sudoku_problem_target.add_prover_binding(&mut builder);
+
+    let circuit = builder.build::<PlonkConfig>();
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problem = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+    let (prover_id, challenge_nonce) = (
+        BaseField::from_canonical_u64(42),
+        BaseField::from_canonical_u64(20261018),
+    );
+    let mut rng = test_rng();
+    let salt = core::array::from_fn(|_| BaseField::from_noncanonical_u64(rng.gen()));
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_problem_target,
+        problem,
+        solution,
+    );
+    super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::set_prover_binding_witness(
+        &mut witness,
+        &binding_target,
+        prover_id,
+        challenge_nonce,
+        salt,
+    );
+
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+
+    let expected_binding = super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::prover_binding(
+        prover_id,
+        challenge_nonce,
+        solution,
+        salt,
+    );
+    assert_eq!(proof.public_inputs[..2], [prover_id, challenge_nonce]);
+    assert_eq!(proof.public_inputs[2..], expected_binding.elements);
+
+    circuit
+        .verify(proof.clone())
+        .expect("Proof verification goes wrong");
+
+    // Someone else trying to pass the proof as their own,
+    // or to answer a different challenge with it.
+    let other_prover_id = BaseField::from_canonical_u64(43);
+    let other_challenge_nonce = BaseField::from_canonical_u64(20261019);
+    for (tampered_prover_id, tampered_challenge_nonce) in [
+        (other_prover_id, challenge_nonce),
+        (prover_id, other_challenge_nonce),
+        (other_prover_id, other_challenge_nonce),
+    ] {
+        let mut copied_proof = proof.clone();
+        copied_proof.public_inputs[0] = tampered_prover_id;
+        copied_proof.public_inputs[1] = tampered_challenge_nonce;
+        circuit
+            .verify(copied_proof.clone())
+            .expect_err("A copied proof was accepted");
+
+        // Recomputing the binding does not help, even knowing the salt.
+        let tampered_binding = super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::prover_binding(
+            tampered_prover_id,
+            tampered_challenge_nonce,
+            solution,
+            salt,
+        );
+        copied_proof.public_inputs[2..].copy_from_slice(&tampered_binding.elements);
+        circuit
+            .verify(copied_proof)
+            .expect_err("A copied proof was accepted");
+    }
+}
+
+/// Tests the commit-reveal scheme: the commitment exposed when proving the
+/// knowledge of a solution is opened by the proof revealing the solution.
+#[test]
+fn test_sudoku_commit_reveal() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let circuit_config =
+        plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_zk_config();
+
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config.clone());
+    let (commit_sudoku_target, commit_target) =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_commitment_to_sudoku_solution(
+            &mut builder,
+        )
+        .expect("Circuit building goes wrong.");
+    let commit_circuit = builder.build::<PlonkConfig>();
+
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config);
+    let (reveal_sudoku_target, reveal_target) =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_reveal_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    let reveal_circuit = builder.build::<PlonkConfig>();
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problem = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+    let mut rng = test_rng();
+    let salt = core::array::from_fn(|_| BaseField::from_noncanonical_u64(rng.gen()));
+
+    let expected_commitment =
+        super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::solution_commitment(solution, salt);
+    let commitment_range = SIZE * SIZE..SIZE * SIZE + 4;
+
+    // Commit phase.
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &commit_sudoku_target,
+        problem,
+        solution,
+    );
+    super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::set_solution_commitment_witness(
+        &mut witness,
+        &commit_target,
+        salt,
+    );
+    let commit_proof = commit_circuit
+        .prove(witness)
+        .expect("proof generation goes wrong");
+
+    assert_eq!(commit_proof.public_inputs.len(), SIZE * SIZE + 4);
+    assert_eq!(
+        commit_proof.public_inputs[commitment_range.clone()],
+        expected_commitment.elements
+    );
+    commit_circuit
+        .verify(commit_proof.clone())
+        .expect("Proof verification goes wrong");
+
+    // Reveal phase.
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &reveal_sudoku_target,
+        problem,
+        solution,
+    );
+    super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::set_solution_commitment_witness(
+        &mut witness,
+        &reveal_target,
+        salt,
+    );
+    let reveal_proof = reveal_circuit
+        .prove(witness)
+        .expect("proof generation goes wrong");
+
+    assert_eq!(
+        reveal_proof.public_inputs[..commitment_range.end],
+        commit_proof.public_inputs[..]
+    );
+    assert_eq!(
+        reveal_proof.public_inputs[commitment_range.end..],
+        Vec::from_iter(
+            solution
+                .iter()
+                .flatten()
+                .map(|&n| BaseField::from_canonical_usize(n))
+        )
+    );
+    reveal_circuit
+        .verify(reveal_proof.clone())
+        .expect("Proof verification goes wrong");
+
+    // Revealing another solution for the same commitment must fail.
+    let mut tampered_proof = reveal_proof;
+    tampered_proof
+        .public_inputs
+        .swap(commitment_range.end, commitment_range.end + 1);
+    reveal_circuit
+        .verify(tampered_proof)
+        .expect_err("A proof revealing the wrong solution was accepted");
+}
+
+#[test]
+fn test_sudoku_equivalence() {
+    const SIZE: usize = 9;
+    const SIZE_SQRT: usize = 3;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+    let equivalence_target =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_equivalence(
+            &mut builder,
+        )
+        .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let solution = [
+        [2, 4, 9, 5, 3, 6, 1, 8, 7],
+        [3, 5, 1, 2, 7, 8, 4, 9, 6],
+        [6, 7, 8, 4, 9, 1, 5, 3, 2],
+        [8, 9, 7, 1, 4, 5, 6, 2, 3],
+        [4, 2, 3, 6, 8, 9, 7, 5, 1],
+        [5, 1, 6, 7, 2, 3, 9, 4, 8],
+        [1, 6, 2, 3, 5, 4, 8, 7, 9],
+        [9, 3, 5, 8, 6, 7, 2, 1, 4],
+        [7, 8, 4, 9, 1, 2, 3, 6, 5],
+    ];
+    // We keep the cells on the diagonals only.
+    let puzzle: [[usize; SIZE]; SIZE] = core::array::from_fn(|i| {
+        core::array::from_fn(|j| {
+            if i == j || i + j == SIZE - 1 {
+                solution[i][j]
+            } else {
+                0
+            }
+        })
+    });
+
+    let symmetry = super::SudokuSymmetry::<SIZE, SIZE_SQRT> {
+        transpose: true,
+        bands: [2, 0, 1],
+        rows_in_bands: [[1, 0, 2], [2, 1, 0], [0, 2, 1]],
+        stacks: [1, 2, 0],
+        columns_in_stacks: [[0, 2, 1], [1, 0, 2], [2, 0, 1]],
+        relabeling: [5, 3, 9, 1, 2, 8, 7, 4, 6],
+    };
+
+    // The symmetry maps solutions to solutions.
+    let transformed_solution = symmetry.apply(solution);
+    let expected_symbols = Vec::from_iter(1..=SIZE);
+    for group in super::SudokuProblemTarget::<SIZE, 0>::get_rows(&transformed_solution)
+        .into_iter()
+        .chain(super::SudokuProblemTarget::<SIZE, 0>::get_columns(
+            &transformed_solution,
+        ))
+        .chain(super::SudokuProblemTarget::<SIZE, SIZE_SQRT>::get_regions(
+            &transformed_solution,
+        ))
+    {
+        let mut symbols = group.to_vec();
+        symbols.sort();
+        assert_eq!(symbols, expected_symbols);
+    }
+
+    let transformed_puzzle = symmetry.apply(puzzle);
+    assert_eq!(
+        super::SudokuSymmetry::<SIZE, SIZE_SQRT>::identity().apply(puzzle),
+        puzzle
+    );
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_equivalence_witness(
+        &mut witness,
+        &equivalence_target,
+        puzzle,
+        transformed_puzzle,
+        symmetry,
+    );
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+    assert_eq!(
+        proof.public_inputs,
+        Vec::from_iter(
+            puzzle
+                .iter()
+                .chain(transformed_puzzle.iter())
+                .flatten()
+                .map(|&n| BaseField::from_canonical_usize(n))
+        )
+    );
+    circuit
+        .verify(proof)
+        .expect("proof verification goes wrong");
+
+    // The proof fails if the second grid is not the image of the first one.
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+    let equivalence_target = super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_equivalence_with_connect_strategy(
+        &mut builder,
+        StandardTestEq::default(),
+    )
+    .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut not_equivalent_puzzle = transformed_puzzle;
+    not_equivalent_puzzle.swap(0, SIZE - 1);
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_equivalence_witness(
+        &mut witness,
+        &equivalence_target,
+        puzzle,
+        not_equivalent_puzzle,
+        symmetry,
+    );
+    let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+    circuit
+        .verify(invalid_proof)
+        .expect_err("A proof for non equivalent puzzles was accepted");
+}
+
+/// Tests the 6x6 variant, whose regions have 2 rows and 3 columns.
+#[test]
+fn test_rectangular_sudoku_regions() {
+    const SIZE: usize = 6;
+    const BOX_ROWS: usize = 2;
+    const BOX_COLS: usize = 3;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let solution = [
+        [1, 2, 3, 4, 5, 6],
+        [4, 5, 6, 1, 2, 3],
+        [2, 3, 1, 5, 6, 4],
+        [5, 6, 4, 2, 3, 1],
+        [3, 1, 2, 6, 4, 5],
+        [6, 4, 5, 3, 1, 2],
+    ];
+    let problem = [
+        [1, 0, 0, 0, 5, 0],
+        [0, 0, 6, 0, 0, 0],
+        [0, 3, 0, 0, 0, 4],
+        [5, 0, 0, 0, 3, 0],
+        [0, 0, 0, 6, 0, 0],
+        [0, 4, 0, 0, 0, 2],
+    ];
+    // Every row and column contains all the symbols, but the regions do not.
+    let latin_square: [[usize; SIZE]; SIZE] =
+        core::array::from_fn(|i| core::array::from_fn(|j| (i + j) % SIZE + 1));
+
+    let regions = super::SudokuProblemTarget::<SIZE, BOX_ROWS, BOX_COLS>::get_regions(&solution);
+    assert_eq!(regions[0], [1, 2, 3, 4, 5, 6]);
+    assert_eq!(regions[1], [4, 5, 6, 1, 2, 3]);
+    assert_eq!(regions[5], [6, 4, 5, 3, 1, 2]);
+
+    let circuit_config =
+        plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_zk_config();
+
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config.clone());
+    let sudoku_target =
+        super::SudokuCircuitBuilder::<SIZE, BOX_ROWS, BOX_COLS>::add_proof_of_sudoku_solution(
+            &mut builder,
+        )
+        .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        problem,
+        solution,
+    );
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+    circuit
+        .verify(proof)
+        .expect("proof verification goes wrong");
+
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config);
+    let sudoku_target = super::SudokuCircuitBuilder::<SIZE, BOX_ROWS, BOX_COLS>::add_proof_of_sudoku_solution_with_connect_strategy(
+        &mut builder,
+        SudokuConstraintStrategy::PermutationGate,
+        StandardTestEq::default(),
+    )
+    .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        [[0; SIZE]; SIZE],
+        latin_square,
+    );
+    let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+    circuit
+        .verify(invalid_proof)
+        .expect_err("An invalid proof was accepted");
+}
+
+/// Tests a 16x16 Sudoku, whose rows, columns and regions are permuted
+/// by permutation gates split across several rows of the circuit.
+#[test]
+fn test_sudoku_16x16() {
+    const SIZE: usize = 16;
+    const SIZE_SQRT: usize = 4;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    // The usual pattern for the solutions of Sudoku grids: every row is
+    // the previous one shifted by `SIZE_SQRT`, and by one more cell when
+    // moving to the next band of regions.
+    let solution: [[usize; SIZE]; SIZE] = core::array::from_fn(|row| {
+        core::array::from_fn(|col| {
+            (SIZE_SQRT * (row % SIZE_SQRT) + row / SIZE_SQRT + col) % SIZE + 1
+        })
+    });
+    let problem: [[usize; SIZE]; SIZE] = core::array::from_fn(|row| {
+        core::array::from_fn(|col| {
+            if (row * 7 + col * 3) % 5 == 0 {
+                solution[row][col]
+            } else {
+                0
+            }
+        })
+    });
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+    let sudoku_target =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    sudoku_target.register_problem_public_inputs(&mut builder);
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        problem,
+        solution,
+    );
+    let proof =
+        prove_with_metrics(&circuit, witness, "sudoku 16x16").expect("proof generation goes wrong");
+    circuit
+        .verify(proof)
+        .expect("proof verification goes wrong");
+}
+
+/// Tests that many puzzles can be proven at once, and compares the time
+/// spent per puzzle with the time needed by single-puzzle proofs.
+#[test]
+fn test_sudoku_batch() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+    const N: usize = 4;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    type BaseField = <PlonkConfig as plonky2::plonk::config::GenericConfig<2>>::F;
+
+    let solutions = [
+        [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]],
+        [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]],
+        [[2, 1, 4, 3], [4, 3, 2, 1], [1, 2, 3, 4], [3, 4, 1, 2]],
+        [[3, 4, 1, 2], [1, 2, 3, 4], [4, 3, 2, 1], [2, 1, 4, 3]],
+    ];
+    let problems = [
+        [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]],
+        [[0, 2, 0, 0], [0, 0, 0, 2], [2, 0, 0, 0], [0, 0, 2, 0]],
+        [[0, 0, 0, 0], [4, 0, 0, 0], [0, 0, 0, 4], [0, 4, 0, 0]],
+        [[3, 0, 0, 0], [0, 0, 3, 0], [0, 3, 0, 0], [0, 0, 0, 3]],
+    ];
+
+    let circuit_config = plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config();
+
+    let batch_circuit =
+        super::SudokuBatchCircuit::<SIZE, SIZE_SQRT, N>::new(circuit_config.clone())
+            .expect("Circuit building goes wrong.");
+
+    let (proof, batch_time) = crate::time_it!(batch_circuit
+        .prove(problems, solutions)
+        .expect("proof generation goes wrong"));
+    assert_eq!(
+        proof.public_inputs,
+        Vec::from_iter(
+            problems
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|&n| BaseField::from_canonical_usize(n))
+        )
+    );
+    batch_circuit
+        .verify(proof)
+        .expect("proof verification goes wrong");
+
+    // The same puzzles, proven one by one.
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, 2>::new(
+        circuit_config.clone(),
+    );
+    let sudoku_target =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let (single_proofs, single_time) =
+        crate::time_it!(Vec::from_iter(problems.into_iter().zip(solutions).map(
+            |(problem, solution)| {
+                let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+                super::SudokuWitnessBuilder::set_sudoku_witness(
+                    &mut witness,
+                    &sudoku_target,
+                    problem,
+                    solution,
+                );
+                circuit.prove(witness).expect("proof generation goes wrong")
+            }
+        )));
+    for proof in single_proofs {
+        circuit
+            .verify(proof)
+            .expect("proof verification goes wrong");
+    }
+
+    println!(
+        "Proving {N} puzzles at once takes {batch_time:?}, i.e. {:?} per puzzle, \
+        while single-puzzle proofs take {:?} per puzzle",
+        batch_time / N as u32,
+        single_time / N as u32,
+    );
+
+    // If the solution in any one slot is wrong, the proof is rejected.
+    let batch_circuit = super::SudokuBatchCircuit::<SIZE, SIZE_SQRT, N>::new_with_connect_strategy(
+        circuit_config,
+        StandardTestEq::default(),
+    )
+    .expect("Circuit building goes wrong.");
+    for slot in 0..N {
+        // Swapping two hidden cells of the first row keeps the row
+        // a permutation of the symbols, but breaks two columns.
+        let hidden_columns = Vec::from_iter((0..SIZE).filter(|&col| problems[slot][0][col] == 0));
+        let mut invalid_solutions = solutions;
+        invalid_solutions[slot][0].swap(hidden_columns[0], hidden_columns[1]);
+        let invalid_proof = batch_circuit
+            .prove(problems, invalid_solutions)
+            .expect("Proof generation fails.");
+        assert!(
+            batch_circuit.verify(invalid_proof).is_err(),
+            "the batch is accepted with a wrong solution in slot {slot}"
+        );
+    }
+}
+
+/// Tests that a proof reveals exactly the cells of the solution selected
+/// by the mask, and that the disclosed cells can not be tampered with.
+#[test]
+fn test_sudoku_cell_disclosure() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_zk_config(),
+        );
+    let (sudoku_target, disclosure_target) =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_disclosure(
+            &mut builder,
+        )
+        .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problem = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+    let mut mask = [[false; SIZE]; SIZE];
+    mask[0][3] = true;
+    mask[2][0] = true;
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        problem,
+        solution,
+    );
+    super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::set_cell_disclosure_witness(
+        &mut witness,
+        &disclosure_target,
+        mask,
+    );
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+
+    let to_field = |grid: [[usize; SIZE]; SIZE]| {
+        Vec::from_iter(
+            grid.iter()
+                .flatten()
+                .map(|&n| BaseField::from_canonical_usize(n)),
+        )
+    };
+    let disclosed = super::SudokuWitnessBuilder::<SIZE, SIZE_SQRT>::disclosed_cells(solution, mask);
+    assert_eq!(disclosed[0], [0, 0, 0, 4]);
+    assert_eq!(disclosed[2], [2, 0, 0, 0]);
+    assert_eq!(
+        proof.public_inputs,
+        [
+            to_field(problem),
+            to_field(mask.map(|row| row.map(usize::from))),
+            to_field(disclosed),
+        ]
+        .concat()
+    );
+    circuit
+        .verify(proof.clone())
+        .expect("Proof verification goes wrong");
+
+    let disclosed_range = 2 * SIZE * SIZE..3 * SIZE * SIZE;
+
+    // A disclosed cell can not be changed.
+    let mut tampered_proof = proof.clone();
+    tampered_proof.public_inputs[disclosed_range.start + 3] = BaseField::from_canonical_usize(3);
+    circuit
+        .verify(tampered_proof)
+        .expect_err("A proof disclosing a wrong cell was accepted");
+
+    // A cell that is not disclosed can not be claimed to be revealed.
+    let mut tampered_proof = proof;
+    tampered_proof.public_inputs[SIZE * SIZE + 1] = BaseField::ONE;
+    tampered_proof.public_inputs[disclosed_range.start + 1] = BaseField::from_canonical_usize(2);
+    circuit
+        .verify(tampered_proof)
+        .expect_err("A proof disclosing a cell outside of the mask was accepted");
+}
+
+/// Tests the lookup table backend, and compares the size of its circuit
+/// and its proving time with the ones of the permutation gate backend.
+#[test]
+fn test_sudoku_constraint_strategies() {
+    const SIZE: usize = 9;
+    const SIZE_SQRT: usize = 3;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let circuit_config = plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config();
+    let mut rng = test_rng();
+    let (_symbols, sudoku_problem_instances) = numeric_setup_values(1, &mut rng);
+    let sudoku_problem_instances = Vec::from_iter(sudoku_problem_instances);
+
+    for strategy in [
+        SudokuConstraintStrategy::PermutationGate,
+        SudokuConstraintStrategy::LookupTable,
+    ] {
+        let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+            BaseField,
+            FIELD_EXTENSION_DEGREE,
+        >::new(circuit_config.clone());
+        let sudoku_target =
+            super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_strategy(
+                &mut builder,
+                strategy,
+            )
+            .expect("Circuit building goes wrong.");
+        // Lookup gates are only added by `build`, so they are not counted here.
+        let num_gates = builder.num_gates();
+        let circuit = builder.build::<PlonkConfig>();
+
+        println!(
+            "{strategy:?}: {num_gates} gates before lookups and padding, \
+            degree 2^{}, constraint degree {}",
+            circuit.common.degree_bits(),
+            circuit.common.constraint_degree(),
+        );
+
+        for (solution, problem) in sudoku_problem_instances.iter() {
+            let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+            super::SudokuWitnessBuilder::set_sudoku_witness(
+                &mut witness,
+                &sudoku_target,
+                *problem,
+                *solution,
+            );
+            let proof = prove_with_metrics(&circuit, witness, &format!("sudoku 9x9, {strategy:?}"))
+                .expect("proof generation goes wrong");
+            circuit
+                .verify(proof)
+                .expect("Proof verification goes wrong");
+        }
+    }
+
+    // Every row and column of this grid contains all the symbols,
+    // but the regions do not.
+    let latin_square: [[usize; SIZE]; SIZE] =
+        core::array::from_fn(|i| core::array::from_fn(|j| (i + j) % SIZE + 1));
+
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config.clone());
+    let sudoku_target = super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_connect_strategy(
+        &mut builder,
+        SudokuConstraintStrategy::LookupTable,
+        StandardTestEq::default(),
+    )
+    .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        [[0; SIZE]; SIZE],
+        latin_square,
+    );
+    let invalid_proof = circuit.prove(witness).expect("Proof generation fails.");
+    circuit
+        .verify(invalid_proof)
+        .expect_err("An invalid proof was accepted");
+
+    // A cell that is not a symbol is rejected by the lookup table.
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    for (row_targets, row_values) in sudoku_target.problem.iter().zip(latin_square) {
+        witness.set_target_arr(row_targets, &row_values.map(|_| BaseField::ZERO));
+    }
+    for (row_targets, row_values) in sudoku_target.solution.iter().zip(latin_square) {
+        witness.set_target_arr(
+            row_targets,
+            &row_values.map(|n| BaseField::from_canonical_usize(n + SIZE)),
+        );
+    }
+    let proof = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| circuit.prove(witness)));
+    assert!(proof.map_or(true, |proof| proof.is_err()));
+
+    // Lookup table entries are `u16` values.
+    let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+        BaseField,
+        FIELD_EXTENSION_DEGREE,
+    >::new(circuit_config);
+    assert!(
+        super::SudokuCircuitBuilder::<25, 5>::add_proof_of_sudoku_solution_with_strategy(
+            &mut builder,
+            SudokuConstraintStrategy::LookupTable,
+        )
+        .is_err()
+    );
+}
+
+/// Checks the lookup table strategy on the largest grids it supports,
+/// where the powers of two of the symbols fill all the 16 bits of the
+/// lookup table entries.
+#[test]
+fn test_sudoku_16x16_lookup_table() {
+    const SIZE: usize = 16;
+    const SIZE_SQRT: usize = 4;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+    let sudoku_target = super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_connect_strategy(
+        &mut builder,
+        SudokuConstraintStrategy::LookupTable,
+        StandardTestEq::default(),
+    )
+    .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PlonkConfig>();
+
+    let solution: [[usize; SIZE]; SIZE] = core::array::from_fn(|row| {
+        core::array::from_fn(|col| {
+            (SIZE_SQRT * (row % SIZE_SQRT) + row / SIZE_SQRT + col) % SIZE + 1
+        })
+    });
+    // Every row and column of this grid contains all the symbols,
+    // but the regions do not.
+    let latin_square: [[usize; SIZE]; SIZE] =
+        core::array::from_fn(|i| core::array::from_fn(|j| (i + j) % SIZE + 1));
+
+    for (grid, is_solution) in [(solution, true), (latin_square, false)] {
+        let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+        super::SudokuWitnessBuilder::set_sudoku_witness(
+            &mut witness,
+            &sudoku_target,
+            [[0; SIZE]; SIZE],
+            grid,
+        );
+        let proof = circuit.prove(witness).expect("Proof generation fails.");
+        assert_eq!(circuit.verify(proof).is_ok(), is_solution);
+    }
+}
+
+/// Checks that no single value of the witness of a Sudoku proof
+/// can be changed without changing the statement or breaking the proof.
+#[test]
+fn test_sudoku_witness_mutations() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problem = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+
+    for strategy in [
+        SudokuConstraintStrategy::PermutationGate,
+        SudokuConstraintStrategy::LookupTable,
+    ] {
+        let mut builder = plonky2::plonk::circuit_builder::CircuitBuilder::<
+            BaseField,
+            FIELD_EXTENSION_DEGREE,
+        >::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+        let sudoku_target = super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution_with_connect_strategy(
+            &mut builder,
+            strategy,
+            StandardTestEq::default(),
+        )
+        .expect("Circuit building goes wrong.");
+        sudoku_target.register_problem_public_inputs(&mut builder);
+        let circuit = builder.build::<PlonkConfig>();
+
+        let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+        super::SudokuWitnessBuilder::set_sudoku_witness(
+            &mut witness,
+            &sudoku_target,
+            problem,
+            solution,
+        );
+        let targets = Vec::from_iter(witness.target_values.keys().copied());
+        crate::utilities::soundness::check_witness_mutations(&circuit, &witness, targets)
+            .unwrap_or_else(|err| panic!("{strategy:?}: {err}"));
+    }
+}
+
+/// Proves the solution of the puzzle stored in `fixtures/sudoku_9x9.txt`,
+/// and checks the public inputs of the proof against the ones stored in
+/// the fixture. The digest of the circuit is checked by
+/// `test_canonical_circuit_fingerprints`, as `sudoku_9x9`.
+#[test]
+fn test_sudoku_fixture() {
+    const SIZE: usize = 9;
+    const SIZE_SQRT: usize = 3;
+
+    type PlonkConfig = plonky2::plonk::config::PoseidonGoldilocksConfig;
+    const FIELD_EXTENSION_DEGREE: usize = 2;
+    type BaseField =
+        <PlonkConfig as plonky2::plonk::config::GenericConfig<FIELD_EXTENSION_DEGREE>>::F;
+
+    let path = fixture_path("sudoku_9x9.txt");
+    let stored_fixture = Fixture::read(&path).expect("the fixture is valid");
+    let puzzle = stored_fixture
+        .grid::<SIZE>("puzzle")
+        .expect("the fixture contains the puzzle");
+    let solution = stored_fixture
+        .grid::<SIZE>("solution")
+        .expect("the fixture contains the solution");
+
+    let mut builder =
+        plonky2::plonk::circuit_builder::CircuitBuilder::<BaseField, FIELD_EXTENSION_DEGREE>::new(
+            plonky2::plonk::circuit_data::CircuitConfig::standard_recursion_config(),
+        );
+    let sudoku_target =
+        super::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    sudoku_target.register_problem_public_inputs(&mut builder);
+    let circuit = builder.build::<PlonkConfig>();
+
+    let mut witness = plonky2::iop::witness::PartialWitness::<BaseField>::new();
+    super::SudokuWitnessBuilder::set_sudoku_witness(&mut witness, &sudoku_target, puzzle, solution);
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+    circuit
+        .verify(proof.clone())
+        .expect("Proof verification goes wrong");
+
+    let as_u64 = |row: [usize; SIZE]| row.map(|cell| cell as u64);
+    Fixture::new(
+        "The 9x9 Sudoku circuit of `test_sudoku_fixture`.\n\
+        `puzzle` and `solution` are the inputs, the other sections are checked.",
+    )
+    .section("puzzle", puzzle.map(as_u64))
+    .section("solution", solution.map(as_u64))
+    .section(
+        "public_inputs",
+        proof
+            .public_inputs
+            .chunks(SIZE)
+            .map(|row| Vec::from_iter(row.iter().map(PrimeField64::to_canonical_u64))),
+    )
+    .check(&path)
+    .expect("the circuit does not match the fixture");
+}
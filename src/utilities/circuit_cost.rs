@@ -0,0 +1,334 @@
+use std::{collections::BTreeMap, fmt};
+
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, CircuitData},
+        config::GenericConfig,
+    },
+};
+
+/// The value of a selector polynomial in the rows of the gates that do not
+/// use it. It mirrors `plonky2::gates::selectors::UNUSED_SELECTOR`,
+/// which is private.
+const UNUSED_SELECTOR: u64 = u32::MAX as u64;
+
+/// The id of the gate used to pad the circuit to a power of two rows.
+const NOOP_GATE_ID: &str = "NoopGate";
+
+/// How much of a circuit is taken by some gates.
+///
+/// It is either the cost of a whole circuit, computed by `of_circuit`,
+/// or the cost of a gadget, computed by `measure_gadget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitCost {
+    /// The number of rows of each type of gate, indexed by the gate id.
+    /// The rows that only pad the circuit are not counted.
+    pub gate_counts: BTreeMap<String, usize>,
+    /// The degree of the constraints of each type of gate in `gate_counts`.
+    pub gate_degrees: BTreeMap<String, usize>,
+    /// The number of rows occupied by the gates, i.e. the sum of `gate_counts`.
+    pub rows: usize,
+    /// The number of routed wires that are connected to other targets.
+    pub routed_wires: usize,
+    /// The highest degree of the constraints of the gates.
+    pub max_degree: usize,
+    /// The base 2 logarithm of the number of rows of the whole circuit,
+    /// padding included.
+    pub degree_bits: usize,
+}
+
+impl CircuitCost {
+    /// Computes the cost of `circuit`.
+    ///
+    /// The builder does not expose its gates, so we read them from the
+    /// selector polynomials of the circuit instead: in every row, the
+    /// selector of the group of the gate in that row is the index of the gate
+    /// in `circuit.common.gates`, and the other selectors are unused.
+    pub fn of_circuit<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+        circuit: &CircuitData<F, C, D>,
+    ) -> Self {
+        let common = &circuit.common;
+        let degree = common.degree();
+
+        // The selector polynomials are the first committed polynomials.
+        let selectors = Vec::from_iter(
+            circuit.prover_only.constants_sigmas_commitment.polynomials
+                [..common.selectors_info.num_selectors()]
+                .iter()
+                .map(|selector| selector.clone().fft()),
+        );
+
+        let mut gate_counts = BTreeMap::new();
+        for row in 0..degree {
+            let gate_idx = selectors
+                .iter()
+                .map(|selector| selector.values[row].to_canonical_u64())
+                .find(|&value| value != UNUSED_SELECTOR)
+                .expect("every row belongs to a selector group");
+            let gate_id = common.gates[gate_idx as usize].0.id();
+            if gate_id != NOOP_GATE_ID {
+                *gate_counts.entry(gate_id).or_insert(0) += 1;
+            }
+        }
+
+        // A routed wire is used if it is connected to some other target,
+        // i.e. if its partition in the copy constraints is not a singleton.
+        // Connections to virtual targets count too, even if the permutation
+        // argument does not see them, since the gadget does not know yet
+        // which wires its inputs will be copied from.
+        let representative_map = &circuit.prover_only.representative_map;
+        let mut partition_sizes = vec![0usize; representative_map.len()];
+        for representative in representative_map.iter() {
+            partition_sizes[*representative] += 1;
+        }
+        let num_wires = common.config.num_wires;
+        let routed_wires = (0..degree)
+            .flat_map(|row| (0..common.config.num_routed_wires).map(move |column| (row, column)))
+            .filter(|(row, column)| {
+                partition_sizes[representative_map[row * num_wires + column]] > 1
+            })
+            .count();
+
+        let gate_degrees = BTreeMap::from_iter(common.gates.iter().filter_map(|gate| {
+            let gate_id = gate.0.id();
+            gate_counts
+                .contains_key(&gate_id)
+                .then(|| (gate_id, gate.0.degree()))
+        }));
+
+        Self::new(
+            gate_counts,
+            gate_degrees,
+            routed_wires,
+            common.degree_bits(),
+        )
+    }
+
+    fn new(
+        gate_counts: BTreeMap<String, usize>,
+        gate_degrees: BTreeMap<String, usize>,
+        routed_wires: usize,
+        degree_bits: usize,
+    ) -> Self {
+        Self {
+            rows: gate_counts.values().sum(),
+            max_degree: gate_degrees.values().copied().max().unwrap_or(0),
+            gate_counts,
+            gate_degrees,
+            routed_wires,
+            degree_bits,
+        }
+    }
+
+    /// The cost of the gates that are in `self`, but not in `baseline`.
+    ///
+    /// `max_degree` only takes into account the types of gates that are
+    /// more in `self` than in `baseline`, while `degree_bits` is the one
+    /// of `self`.
+    pub fn minus_baseline(&self, baseline: &Self) -> Self {
+        let gate_counts = BTreeMap::from_iter(self.gate_counts.iter().filter_map(|(id, count)| {
+            let baseline_count = baseline.gate_counts.get(id).copied().unwrap_or(0);
+            (*count > baseline_count).then(|| (id.clone(), count - baseline_count))
+        }));
+        let gate_degrees = BTreeMap::from_iter(
+            self.gate_degrees
+                .iter()
+                .filter(|(id, _)| gate_counts.contains_key(*id))
+                .map(|(id, degree)| (id.clone(), *degree)),
+        );
+
+        Self::new(
+            gate_counts,
+            gate_degrees,
+            self.routed_wires.saturating_sub(baseline.routed_wires),
+            self.degree_bits,
+        )
+    }
+}
+
+/// Measures the cost of the gates that `gadget` adds to a circuit built
+/// with `config`.
+///
+/// A `CircuitBuilder` cannot be cloned, nor inspected before it is built,
+/// so we build two circuits: an empty one, and one that only contains
+/// `gadget`. The cost of the gadget is the difference of their costs,
+/// which discounts the gates every circuit has, like `PublicInputGate`.
+/// The targets the gadget needs as inputs should be created by `gadget`
+/// itself, with `add_virtual_target` and the like, which cost nothing.
+pub fn measure_gadget<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    config: &CircuitConfig,
+    gadget: impl FnOnce(&mut CircuitBuilder<F, D>),
+) -> CircuitCost {
+    let baseline =
+        CircuitCost::of_circuit(&CircuitBuilder::<F, D>::new(config.clone()).build::<C>());
+
+    let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+    gadget(&mut builder);
+    let cost = CircuitCost::of_circuit(&builder.build::<C>());
+
+    cost.minus_baseline(&baseline)
+}
+
+/// A table of named `CircuitCost`s, to compare gadgets side by side.
+///
+/// It is printed with `Display`: one line per entry, followed by one
+/// line per type of gate.
+#[derive(Debug, Clone, Default)]
+pub struct CostTable {
+    entries: Vec<(String, CircuitCost)>,
+}
+
+impl CostTable {
+    /// The gate ids can be long, e.g. the one of `PermutationGate` contains
+    /// the whole swap schedule, so they are truncated to this many chars.
+    const MAX_GATE_ID_LEN: usize = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a line to the table.
+    pub fn add(&mut self, name: impl Into<String>, cost: CircuitCost) -> &mut Self {
+        self.entries.push((name.into(), cost));
+        self
+    }
+
+    pub fn entries(&self) -> &[(String, CircuitCost)] {
+        &self.entries
+    }
+}
+
+impl fmt::Display for CostTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .entries
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("gadget".len());
+
+        writeln!(
+            f,
+            "{:<name_width$} | {:>8} | {:>12} | {:>10} | {:>11}",
+            "gadget", "rows", "routed wires", "max degree", "degree bits"
+        )?;
+        for (name, cost) in self.entries.iter() {
+            writeln!(
+                f,
+                "{:<name_width$} | {:>8} | {:>12} | {:>10} | {:>11}",
+                name, cost.rows, cost.routed_wires, cost.max_degree, cost.degree_bits
+            )?;
+            for (gate_id, count) in cost.gate_counts.iter() {
+                let gate_id = match gate_id.char_indices().nth(Self::MAX_GATE_ID_LEN) {
+                    Some((end, _)) => format!("{}...", &gate_id[..end]),
+                    None => gate_id.clone(),
+                };
+                writeln!(f, "{:<name_width$} | {count:>8} x {gate_id}", "")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::{circuit_data::CircuitConfig, config::PoseidonGoldilocksConfig};
+
+    use super::{measure_gadget, CircuitCost, CostTable};
+    use crate::{
+        factorial::{recursive_product_circuit, BaseField, ConsecutiveProduct, D},
+        permutation::{
+            ApplyPermutation, BubbleSortSwapSchedule, PermutationGate, RecusriveSplitTwoSchedule,
+            SwapSchedule,
+        },
+        sudoku::{SudokuCircuitBuilder, SudokuConstraintStrategy},
+    };
+
+    type PGConfig = PoseidonGoldilocksConfig;
+
+    /// Adds a single permutation gate for `N_OBJECTS` items, with the swap
+    /// schedule `S`, to compare the schedules.
+    fn permutation_gate_cost<S: SwapSchedule, const N_OBJECTS: usize>(
+        config: &CircuitConfig,
+    ) -> CircuitCost {
+        measure_gadget::<BaseField, PGConfig, D>(config, |builder| {
+            let gate = PermutationGate::try_new(N_OBJECTS, S::get_swap_sequence(N_OBJECTS), true)
+                .expect("the schedule is valid");
+            builder.add_gate(gate, vec![]);
+        })
+    }
+
+    /// Measures the gadgets of the crate, and checks the numbers we know
+    /// for sure. Run with `--nocapture` to see the table.
+    #[test]
+    fn test_circuit_cost_report() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut table = CostTable::new();
+
+        let bubble_sort = permutation_gate_cost::<BubbleSortSwapSchedule, 6>(&config);
+        let split_two = permutation_gate_cost::<RecusriveSplitTwoSchedule, 6>(&config);
+        // A single gate takes a single row, whatever the schedule.
+        assert_eq!(bubble_sort.rows, 1);
+        assert_eq!(split_two.rows, 1);
+        // Nothing is connected to the gate, so it uses no routed wire.
+        assert_eq!(bubble_sort.routed_wires, 0);
+        table.add("PermutationGate, 6 items, bubble sort", bubble_sort);
+        table.add("PermutationGate, 6 items, recursive split", split_two);
+
+        let permutation = measure_gadget::<BaseField, PGConfig, D>(&config, |builder| {
+            let n_selectors = builder.permutation_swap_schedule_length(9);
+            let inputs = builder.add_virtual_targets(9);
+            let selectors = builder.add_virtual_targets(n_selectors);
+            let outputs = builder.add_virtual_targets(9);
+            builder
+                .add_permutation_gate(&inputs, &selectors, &outputs, true)
+                .expect("the gate fits in the circuit");
+        });
+        assert!(permutation
+            .gate_counts
+            .keys()
+            .all(|id| id.starts_with("PermutationGate")));
+        // Every input, selector and output is copied to a gate wire.
+        assert!(permutation.routed_wires >= 2 * 9);
+        table.add("add_permutation_gate, 9 items", permutation);
+
+        for (name, strategy) in [
+            (
+                "Sudoku 9x9, permutation gates",
+                SudokuConstraintStrategy::PermutationGate,
+            ),
+            (
+                "Sudoku 9x9, lookup table",
+                SudokuConstraintStrategy::LookupTable,
+            ),
+        ] {
+            let sudoku = measure_gadget::<BaseField, PGConfig, D>(&config, |builder| {
+                SudokuCircuitBuilder::<9, 3>::add_proof_of_sudoku_solution_with_strategy(
+                    builder, strategy,
+                )
+                .expect("Circuit building goes wrong.");
+            });
+            assert!(sudoku.rows <= 1 << sudoku.degree_bits);
+            table.add(name, sudoku);
+        }
+
+        let product = measure_gadget::<BaseField, PGConfig, D>(&config, |builder| {
+            ConsecutiveProduct::new(builder, 32);
+        });
+        assert!(product
+            .gate_counts
+            .keys()
+            .all(|id| id.starts_with("ArithmeticGate")));
+        table.add("ConsecutiveProduct::new, 32 factors", product);
+
+        let recursive_step = recursive_product_circuit().recursive_step_cost();
+        assert!(recursive_step.rows <= 1 << recursive_step.degree_bits);
+        table.add("recursive product step", recursive_step);
+
+        println!("{table}");
+    }
+}
@@ -0,0 +1,161 @@
+use std::ops::RangeInclusive;
+
+use plonky2::{
+    hash::{hash_types::HashOut, merkle_tree::MerkleCap, poseidon::PoseidonHash},
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::{CircuitConfig, VerifierOnlyCircuitData},
+    },
+};
+
+use crate::{
+    factorial::{recursive_product_circuit, BaseField, PGConfig, D},
+    permutation::ApplyPermutation,
+    sudoku::SudokuCircuitBuilder,
+};
+
+/// The values that identify a circuit for a verifier: if any of them
+/// changes, proofs of the old circuit are not accepted anymore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitFingerprint {
+    pub circuit_digest: HashOut<BaseField>,
+    pub constants_sigmas_cap: MerkleCap<BaseField, PoseidonHash>,
+}
+
+impl CircuitFingerprint {
+    pub fn of_verifier_data(verifier_only: &VerifierOnlyCircuitData<PGConfig, D>) -> Self {
+        Self {
+            circuit_digest: verifier_only.circuit_digest,
+            constants_sigmas_cap: verifier_only.constants_sigmas_cap.clone(),
+        }
+    }
+}
+
+/// The circuits of this crate whose fingerprints are recorded in
+/// `fixtures/circuit_digests.txt`, so that unintended changes of their
+/// verifier data are caught by the tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalCircuit {
+    /// A 4x4 Sudoku, whose problem is the public input.
+    Sudoku4x4,
+    /// A 9x9 Sudoku, whose problem is the public input.
+    Sudoku9x9,
+    /// A `PermutationGate` with public inputs and outputs, and private
+    /// swap selectors.
+    Permutation { n_objects: usize },
+    /// The cyclically recursive circuit of `recursive_product_circuit`.
+    RecursiveProductStep,
+    /// The circuit that wraps the proofs of `RecursiveProductStep`.
+    ProductWrapper,
+}
+
+impl CanonicalCircuit {
+    /// The numbers of objects of the canonical permutation circuits.
+    pub const PERMUTATION_SIZES: RangeInclusive<usize> = 4..=16;
+
+    pub fn all() -> Vec<Self> {
+        let mut circuits = vec![Self::Sudoku4x4, Self::Sudoku9x9];
+        circuits.extend(Self::PERMUTATION_SIZES.map(|n_objects| Self::Permutation { n_objects }));
+        circuits.extend([Self::RecursiveProductStep, Self::ProductWrapper]);
+        circuits
+    }
+
+    /// The name of the circuit in the fixture.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Sudoku4x4 => "sudoku_4x4".to_owned(),
+            Self::Sudoku9x9 => "sudoku_9x9".to_owned(),
+            Self::Permutation { n_objects } => format!("permutation_{n_objects}"),
+            Self::RecursiveProductStep => "recursive_product_step".to_owned(),
+            Self::ProductWrapper => "product_wrapper".to_owned(),
+        }
+    }
+
+    /// Builds the circuit and returns its fingerprint.
+    ///
+    /// The product circuits are only built once per process,
+    /// see `recursive_product_circuit`.
+    pub fn fingerprint(&self) -> CircuitFingerprint {
+        match self {
+            Self::Sudoku4x4 => sudoku_fingerprint::<4, 2>(),
+            Self::Sudoku9x9 => sudoku_fingerprint::<9, 3>(),
+            Self::Permutation { n_objects } => permutation_fingerprint(*n_objects),
+            Self::RecursiveProductStep => CircuitFingerprint::of_verifier_data(
+                recursive_product_circuit().recursive_step_verifier_only_data(),
+            ),
+            Self::ProductWrapper => CircuitFingerprint::of_verifier_data(
+                recursive_product_circuit().verifier_only_data(),
+            ),
+        }
+    }
+}
+
+fn sudoku_fingerprint<const SIZE: usize, const BOX_SIZE: usize>() -> CircuitFingerprint {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let sudoku_target =
+        SudokuCircuitBuilder::<SIZE, BOX_SIZE>::add_proof_of_sudoku_solution(&mut builder)
+            .expect("Circuit building goes wrong.");
+    sudoku_target.register_problem_public_inputs(&mut builder);
+    CircuitFingerprint::of_verifier_data(&builder.build::<PGConfig>().verifier_only)
+}
+
+fn permutation_fingerprint(n_objects: usize) -> CircuitFingerprint {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let inputs = builder.add_virtual_targets(n_objects);
+    let outputs = builder.add_virtual_targets(n_objects);
+    let n_swaps = builder.permutation_swap_schedule_length(n_objects);
+    let swap_selectors = builder.add_virtual_targets(n_swaps);
+    builder
+        .add_permutation_gate(&inputs, &swap_selectors, &outputs, true)
+        .expect("Circuit building goes wrong.");
+    builder.register_public_inputs(&inputs);
+    builder.register_public_inputs(&outputs);
+    CircuitFingerprint::of_verifier_data(&builder.build::<PGConfig>().verifier_only)
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::{field::types::PrimeField64, hash::hash_types::HashOut};
+
+    use super::CanonicalCircuit;
+    use crate::{
+        factorial::BaseField,
+        utilities::fixtures::{fixture_path, Fixture},
+    };
+
+    /// The fingerprints of `circuits`, laid out as a fixture with the sections
+    /// `<name>/circuit_digest` and `<name>/constants_sigmas_cap` for every
+    /// circuit. The cap has one line per hash.
+    fn fingerprints_fixture(circuits: &[CanonicalCircuit]) -> Fixture {
+        let to_u64 = |hash: &HashOut<BaseField>| hash.elements.map(|e| e.to_canonical_u64());
+        circuits.iter().fold(
+            Fixture::new(
+                "The fingerprints of the canonical circuits, see `CanonicalCircuit`.\n\
+                After an intended change of a circuit, update them with\n\
+                UPDATE_FIXTURES=1 cargo test --release test_canonical_circuit_fingerprints",
+            ),
+            |fixture, circuit| {
+                let name = circuit.name();
+                let fingerprint = circuit.fingerprint();
+                fixture
+                    .section(
+                        &format!("{name}/circuit_digest"),
+                        [to_u64(&fingerprint.circuit_digest)],
+                    )
+                    .section(
+                        &format!("{name}/constants_sigmas_cap"),
+                        fingerprint.constants_sigmas_cap.0.iter().map(to_u64),
+                    )
+            },
+        )
+    }
+
+    #[test]
+    fn test_canonical_circuit_fingerprints() {
+        fingerprints_fixture(&CanonicalCircuit::all())
+            .check(fixture_path("circuit_digests.txt"))
+            .expect("the fingerprints of the canonical circuits have changed");
+    }
+}
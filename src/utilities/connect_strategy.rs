@@ -0,0 +1,145 @@
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{
+        generator::{GeneratedValues, SimpleGenerator},
+        target::Target,
+        witness::{PartitionWitness, Witness, WitnessWrite},
+    },
+    plonk::{circuit_builder::CircuitBuilder, circuit_data::CommonCircuitData},
+    util::serialization::{Buffer, IoResult, Read, Write},
+};
+
+/// How a gadget enforces two targets to be equal.
+///
+/// The usual way is `CircuitBuilder::connect`, which adds a copy constraint.
+/// When the witness breaks a copy constraint, the prover panics during
+/// witness generation, so there is no proof to show to the verifier.
+/// This is what we want in production, but it prevents us from testing
+/// that the verifier would reject such a witness.
+///
+/// The gadgets of this crate that enforce equalities, or that a value is
+/// not zero, have a variant that takes a `ConnectStrategy`. Building them with `TestEq` instead of
+/// `CopyConstraint` turns the equalities into gate constraints, so that
+/// invalid witnesses yield proofs that fail verification.
+/// See `utilities::soundness` for a driver that takes advantage of it.
+pub trait ConnectStrategy: Copy {
+    fn connect<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: Target,
+        rhs: Target,
+    );
+
+    fn assert_zero<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        let zero = builder.zero();
+        self.connect(builder, target, zero);
+    }
+
+    fn assert_one<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        let one = builder.one();
+        self.connect(builder, target, one);
+    }
+
+    /// Enforces `target` not to be zero, by showing that it has an inverse.
+    ///
+    /// The inverse is computed by `InverseOrZeroGenerator`, which does not
+    /// panic when `target` is zero, and the product of `target` and its
+    /// inverse is enforced to be one through `assert_one`.
+    fn assert_nonzero<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        let inverse = builder.add_virtual_target();
+        builder.add_simple_generator(InverseOrZeroGenerator { target, inverse });
+        let product = builder.mul(target, inverse);
+        self.assert_one(builder, product);
+    }
+}
+
+/// The strategy used in production: equalities are copy constraints.
+#[derive(Debug, Clone, Copy)]
+pub struct CopyConstraint;
+
+impl ConnectStrategy for CopyConstraint {
+    fn connect<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: Target,
+        rhs: Target,
+    ) {
+        builder.connect(lhs, rhs);
+    }
+
+    fn assert_zero<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        builder.assert_zero(target);
+    }
+
+    fn assert_one<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        builder.assert_one(target);
+    }
+
+    fn assert_nonzero<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        target: Target,
+    ) {
+        builder.inverse(target);
+    }
+}
+
+/// Sets `inverse` to the inverse of `target`, or to zero if `target` is zero.
+///
+/// Unlike the generator behind `CircuitBuilder::inverse`, it does not panic
+/// on zero, so that the constraints decide whether the witness is valid.
+#[derive(Debug, Clone)]
+pub struct InverseOrZeroGenerator {
+    pub target: Target,
+    pub inverse: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for InverseOrZeroGenerator
+{
+    fn id(&self) -> String {
+        "InverseOrZeroGenerator".to_owned()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.target]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let value = witness.get_target(self.target);
+        out_buffer.set_target(self.inverse, value.try_inverse().unwrap_or(F::ZERO));
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.target)?;
+        dst.write_target(self.inverse)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        Ok(Self {
+            target: src.read_target()?,
+            inverse: src.read_target()?,
+        })
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, ensure, Context};
+
+/// The environment variable that makes `Fixture::check` overwrite the
+/// fixtures instead of comparing them.
+pub const UPDATE_FIXTURES_ENV_VAR: &str = "UPDATE_FIXTURES";
+
+/// The path of the fixture named `name` in the `fixtures` directory
+/// of this crate.
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+/// Golden values checked into the repository, to detect unintended
+/// changes of the circuits: puzzles, solutions, public inputs and
+/// circuit digests.
+///
+/// A fixture is a text file made of named sections of numbers:
+///
+/// ``` text
+/// # Comments start with `#`.
+/// public_inputs:
+/// 1 0 0 4
+/// 0 0 1 0
+/// ```
+///
+/// The numbers of a section can span several lines, which only matter
+/// for readability, e.g. to lay out the rows of a grid.
+/// Sections keep the order in which they are added.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fixture {
+    header: Vec<String>,
+    sections: Vec<(String, Vec<Vec<u64>>)>,
+}
+
+impl Fixture {
+    /// An empty fixture, whose file starts with the comment lines `header`.
+    pub fn new(header: &str) -> Self {
+        Self {
+            header: Vec::from_iter(header.lines().map(str::to_owned)),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Appends a section, whose lines are `lines`.
+    pub fn section<I: IntoIterator<Item = u64>>(
+        mut self,
+        name: &str,
+        lines: impl IntoIterator<Item = I>,
+    ) -> Self {
+        let lines = Vec::from_iter(lines.into_iter().map(Vec::from_iter));
+        self.sections.push((name.to_owned(), lines));
+        self
+    }
+
+    /// The numbers of the section `name`, regardless of their line breaks.
+    pub fn values(&self, name: &str) -> anyhow::Result<Vec<u64>> {
+        self.lines(name)
+            .map(|lines| lines.concat())
+            .ok_or_else(|| anyhow!("the fixture has no section `{name}`"))
+    }
+
+    /// The section `name`, as a `SIZE`x`SIZE` grid.
+    pub fn grid<const SIZE: usize>(&self, name: &str) -> anyhow::Result<[[usize; SIZE]; SIZE]> {
+        let values = self.values(name)?;
+        ensure!(
+            values.len() == SIZE * SIZE,
+            "the section `{name}` has {} values, expected {}",
+            values.len(),
+            SIZE * SIZE
+        );
+        Ok(core::array::from_fn(|row| {
+            core::array::from_fn(|col| values[row * SIZE + col] as usize)
+        }))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for line in self.header.iter() {
+            text.push_str(&format!("# {line}\n"));
+        }
+        for (name, lines) in self.sections.iter() {
+            text.push_str(&format!("\n{name}:\n"));
+            for line in lines {
+                text.push_str(&line_to_text(line));
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> anyhow::Result<Self> {
+        let mut fixture = Self::default();
+        for (line_nr, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if fixture.sections.is_empty() {
+                    fixture.header.push(comment.trim_start().to_owned());
+                }
+            } else if let Some(name) = line.strip_suffix(':') {
+                fixture.sections.push((name.to_owned(), Vec::new()));
+            } else if !line.is_empty() {
+                let Some((_, lines)) = fixture.sections.last_mut() else {
+                    bail!("line {}: values outside of a section", line_nr + 1);
+                };
+                let values = line
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("line {}: invalid value", line_nr + 1))?;
+                lines.push(values);
+            }
+        }
+        Ok(fixture)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read the fixture {}", path.display()))?;
+        Self::from_text(&text).with_context(|| format!("{} is not a valid fixture", path.display()))
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_text())
+            .with_context(|| format!("cannot write the fixture {}", path.display()))
+    }
+
+    /// Checks that `self` has the same sections as the fixture stored
+    /// at `path`. The comments are not compared.
+    ///
+    /// The error lists every section that does not match, with the lines
+    /// that differ, prefixed by `-` for the stored ones and by `+` for
+    /// the ones of `self`.
+    ///
+    /// When the `UPDATE_FIXTURES` environment variable is set, the
+    /// stored fixture is overwritten with `self` instead. This is the way
+    /// to go after an intended change of a circuit.
+    pub fn check(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if std::env::var_os(UPDATE_FIXTURES_ENV_VAR).is_some() {
+            return self.write(path);
+        }
+
+        let stored = Self::read(path)?;
+        let mut diff = String::new();
+        for (name, lines) in self.sections.iter() {
+            match stored.lines(name) {
+                Some(stored_lines) if stored_lines.concat() == lines.concat() => {}
+                Some(stored_lines) => {
+                    diff.push_str(&format!("{name}:\n"));
+                    for line_nr in 0..stored_lines.len().max(lines.len()) {
+                        let (stored_line, line) = (stored_lines.get(line_nr), lines.get(line_nr));
+                        if stored_line != line {
+                            if let Some(stored_line) = stored_line {
+                                diff.push_str(&format!("- {}\n", line_to_text(stored_line)));
+                            }
+                            if let Some(line) = line {
+                                diff.push_str(&format!("+ {}\n", line_to_text(line)));
+                            }
+                        }
+                    }
+                }
+                None => diff.push_str(&format!("{name}: not in the stored fixture\n")),
+            }
+        }
+        for (name, _) in stored.sections.iter() {
+            if self.lines(name).is_none() {
+                diff.push_str(&format!("{name}: only in the stored fixture\n"));
+            }
+        }
+
+        ensure!(
+            diff.is_empty(),
+            "{} does not match; if the change is intended, \
+            run the tests again with {UPDATE_FIXTURES_ENV_VAR}=1\n{diff}",
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn lines(&self, name: &str) -> Option<&[Vec<u64>]> {
+        self.sections
+            .iter()
+            .find(|(section_name, _)| section_name == name)
+            .map(|(_, lines)| lines.as_slice())
+    }
+}
+
+fn line_to_text(line: &[u64]) -> String {
+    Vec::from_iter(line.iter().map(u64::to_string)).join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixture;
+
+    #[test]
+    fn test_fixture_round_trip() {
+        let fixture = Fixture::new("A 2x2 grid.\nAnd a digest.")
+            .section("grid", [[1, 2], [3, 4]])
+            .section("digest", [[u64::MAX, 0]]);
+
+        let text = fixture.to_text();
+        assert_eq!(
+            text,
+            "# A 2x2 grid.\n# And a digest.\n\ngrid:\n1 2\n3 4\n\ndigest:\n18446744073709551615 0\n"
+        );
+        assert_eq!(
+            Fixture::from_text(&text).expect("the text is valid"),
+            fixture
+        );
+        assert_eq!(
+            fixture.grid::<2>("grid").expect("the grid is 2x2"),
+            [[1, 2], [3, 4]]
+        );
+        assert!(fixture.grid::<3>("grid").is_err());
+        assert!(fixture.values("solution").is_err());
+
+        let path = std::env::temp_dir().join(format!("fixture_{}.txt", std::process::id()));
+        fixture.write(&path).expect("the fixture can be written");
+        fixture.check(&path).expect("a fixture matches itself");
+        let err = Fixture::new("")
+            .section("grid", [[1, 2], [3, 5]])
+            .check(&path)
+            .expect_err("the grids are different");
+        let err = err.to_string();
+        assert!(err.contains("grid:\n- 3 4\n+ 3 5\n"), "{err}");
+        assert!(err.contains("digest: only in the stored fixture"), "{err}");
+        std::fs::remove_file(&path).expect("the fixture can be removed");
+
+        assert!(Fixture::from_text("1 2 3").is_err());
+        assert!(Fixture::from_text("grid:\n1 two").is_err());
+    }
+}
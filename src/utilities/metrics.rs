@@ -0,0 +1,382 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::Context;
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{generator::generate_partial_witness, witness::PartialWitness},
+    plonk::{
+        circuit_data::CircuitData, config::GenericConfig, proof::ProofWithPublicInputs,
+        prover::prove_with_partition_witness,
+    },
+    util::timing::TimingTree,
+};
+
+/// The phases of the life of a proof that we measure the duration of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    CircuitBuild,
+    WitnessGeneration,
+    Proving,
+    Verification,
+}
+
+impl Phase {
+    /// The name of the phase in the serialized metrics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::CircuitBuild => "circuit_build",
+            Phase::WitnessGeneration => "witness_generation",
+            Phase::Proving => "proving",
+            Phase::Verification => "verification",
+        }
+    }
+}
+
+/// A single measurement, taken while running `workflow`.
+///
+/// The workflow is a free form name that identifies what is being measured,
+/// e.g. `"sudoku 9x9"` or `"product/recursive step"`, so that the metrics
+/// of different workflows can be told apart even when they are recorded
+/// concurrently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Metric {
+    /// The time spent in `phase`.
+    Span {
+        workflow: String,
+        phase: Phase,
+        duration: Duration,
+    },
+    /// The size of a proof serialized with `ProofWithPublicInputs::to_bytes`.
+    ProofSize { workflow: String, bytes: usize },
+}
+
+impl Metric {
+    pub fn workflow(&self) -> &str {
+        match self {
+            Metric::Span { workflow, .. } | Metric::ProofSize { workflow, .. } => workflow,
+        }
+    }
+
+    /// The name of the measured quantity: the name of the phase for spans,
+    /// and `proof_size` for proof sizes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Span { phase, .. } => phase.name(),
+            Metric::ProofSize { .. } => "proof_size",
+        }
+    }
+
+    /// The measured value, in the unit given by `unit`.
+    pub fn value(&self) -> f64 {
+        match self {
+            Metric::Span { duration, .. } => duration.as_secs_f64(),
+            Metric::ProofSize { bytes, .. } => *bytes as f64,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Metric::Span { .. } => "s",
+            Metric::ProofSize { .. } => "bytes",
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Metric::Span {
+                workflow,
+                phase,
+                duration,
+            } => write!(f, "{workflow}: {} takes {duration:?}", phase.name()),
+            Metric::ProofSize { workflow, bytes } => {
+                write!(f, "{workflow}: proof size is {bytes} bytes")
+            }
+        }
+    }
+}
+
+/// Collects the metrics reported by the workflows of this crate.
+///
+/// The workflows report into the collector returned by `global_metrics`,
+/// usually through `time_it!`. The collected metrics can be serialized
+/// to JSON or CSV, to compare them across revisions of the crate.
+///
+/// A disabled collector drops the metrics reported to it.
+#[derive(Debug)]
+pub struct MetricsCollector {
+    enabled: AtomicBool,
+    metrics: Mutex<Vec<Metric>>,
+}
+
+static GLOBAL_METRICS: MetricsCollector = MetricsCollector::disabled();
+
+/// The collector the workflows of this crate report into.
+///
+/// It is disabled until `MetricsCollector::enable` is called on it, so that
+/// a long running process does not accumulate metrics nobody reads.
+pub fn global_metrics() -> &'static MetricsCollector {
+    &GLOBAL_METRICS
+}
+
+impl Default for MetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsCollector {
+    /// An enabled collector.
+    pub const fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            metrics: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// A collector that drops the metrics until it is enabled.
+    pub const fn disabled() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            metrics: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops recording the metrics. The ones recorded so far are kept.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn record(&self, metric: Metric) {
+        if !self.is_enabled() {
+            return;
+        }
+        log::debug!("{metric}");
+        self.lock().push(metric);
+    }
+
+    pub fn record_span(&self, workflow: &str, phase: Phase, duration: Duration) {
+        self.record(Metric::Span {
+            workflow: workflow.to_owned(),
+            phase,
+            duration,
+        });
+    }
+
+    pub fn record_proof_size(&self, workflow: &str, bytes: usize) {
+        self.record(Metric::ProofSize {
+            workflow: workflow.to_owned(),
+            bytes,
+        });
+    }
+
+    /// A copy of the metrics recorded so far, in recording order.
+    pub fn metrics(&self) -> Vec<Metric> {
+        self.lock().clone()
+    }
+
+    /// The metrics recorded so far for `workflow`, in recording order.
+    pub fn workflow_metrics(&self, workflow: &str) -> Vec<Metric> {
+        Vec::from_iter(
+            self.lock()
+                .iter()
+                .filter(|metric| metric.workflow() == workflow)
+                .cloned(),
+        )
+    }
+
+    /// Removes and returns the metrics recorded so far.
+    pub fn take(&self) -> Vec<Metric> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn to_json(&self) -> String {
+        metrics_to_json(&self.lock())
+    }
+
+    pub fn to_csv(&self) -> String {
+        metrics_to_csv(&self.lock())
+    }
+
+    /// Writes the metrics recorded so far to `path`, as CSV if the file
+    /// extension is `csv`, and as JSON otherwise.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let contents = match path.extension() {
+            Some(extension) if extension == "csv" => self.to_csv(),
+            _ => self.to_json(),
+        };
+        std::fs::write(path, contents)
+            .with_context(|| format!("cannot write the metrics to {}", path.display()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Metric>> {
+        // A panic while holding the lock cannot leave the vector in an
+        // inconsistent state, so we can ignore the poisoning.
+        self.metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Serializes `metrics` as a JSON array of objects with the fields
+/// `workflow`, `metric`, `value` and `unit`.
+pub fn metrics_to_json(metrics: &[Metric]) -> String {
+    let objects = Vec::from_iter(metrics.iter().map(|metric| {
+        format!(
+            "  {{\"workflow\": {}, \"metric\": \"{}\", \"value\": {}, \"unit\": \"{}\"}}",
+            json_string(metric.workflow()),
+            metric.name(),
+            metric.value(),
+            metric.unit()
+        )
+    }));
+    if objects.is_empty() {
+        "[]\n".to_owned()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+/// Serializes `metrics` as CSV, with the header `workflow,metric,value,unit`.
+pub fn metrics_to_csv(metrics: &[Metric]) -> String {
+    let mut csv = String::from("workflow,metric,value,unit\n");
+    for metric in metrics {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(metric.workflow()),
+            metric.name(),
+            metric.value(),
+            metric.unit()
+        ));
+    }
+    csv
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Like `CircuitData::prove`, but it reports the duration of the witness
+/// generation and of the proving, and the size of the proof, to
+/// `global_metrics` under the name `workflow`, if it is enabled.
+pub fn prove_with_metrics<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    circuit: &CircuitData<F, C, D>,
+    witness: PartialWitness<F>,
+    workflow: &str,
+) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+    let partition_witness = crate::time_it! {
+        generate_partial_witness(witness, &circuit.prover_only, &circuit.common);
+        Phase::WitnessGeneration, workflow
+    };
+    let proof = crate::time_it! {
+        prove_with_partition_witness(
+            &circuit.prover_only,
+            &circuit.common,
+            partition_witness,
+            &mut TimingTree::default(),
+        )?;
+        Phase::Proving, workflow
+    };
+    if global_metrics().is_enabled() {
+        global_metrics().record_proof_size(workflow, proof.to_bytes().len());
+    }
+    Ok(proof)
+}
+
+/// Like `CircuitData::verify`, but it reports the duration of the
+/// verification to `global_metrics` under the name `workflow`, if it is
+/// enabled.
+pub fn verify_with_metrics<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    circuit: &CircuitData<F, C, D>,
+    proof: ProofWithPublicInputs<F, C, D>,
+    workflow: &str,
+) -> anyhow::Result<()> {
+    crate::time_it! {
+        circuit.verify(proof);
+        Phase::Verification, workflow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{MetricsCollector, Phase};
+
+    #[test]
+    fn test_metrics_serialization() {
+        let collector = MetricsCollector::new();
+        collector.record_span("sudoku 9x9", Phase::Proving, Duration::from_millis(1500));
+        collector.record_proof_size("sudoku 9x9", 1024);
+        collector.record_span("product, \"wrapper\"", Phase::Verification, Duration::ZERO);
+
+        assert_eq!(
+            collector.to_csv(),
+            "workflow,metric,value,unit\n\
+             sudoku 9x9,proving,1.5,s\n\
+             sudoku 9x9,proof_size,1024,bytes\n\
+             \"product, \"\"wrapper\"\"\",verification,0,s\n"
+        );
+        assert_eq!(
+            collector.to_json(),
+            "[\n  \
+             {\"workflow\": \"sudoku 9x9\", \"metric\": \"proving\", \"value\": 1.5, \"unit\": \"s\"},\n  \
+             {\"workflow\": \"sudoku 9x9\", \"metric\": \"proof_size\", \"value\": 1024, \"unit\": \"bytes\"},\n  \
+             {\"workflow\": \"product, \\\"wrapper\\\"\", \"metric\": \"verification\", \"value\": 0, \"unit\": \"s\"}\n\
+             ]\n"
+        );
+
+        assert_eq!(collector.workflow_metrics("sudoku 9x9").len(), 2);
+        assert_eq!(collector.take().len(), 3);
+        assert_eq!(collector.to_json(), "[]\n");
+
+        collector.disable();
+        collector.record_proof_size("sudoku 9x9", 1024);
+        assert!(collector.metrics().is_empty());
+    }
+}
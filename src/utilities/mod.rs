@@ -1,31 +1,47 @@
 pub mod test_connect_gate;
 
+pub mod connect_strategy;
+
+pub mod soundness;
+
+pub mod circuit_cost;
+
+pub mod metrics;
+
+#[cfg(test)]
+pub mod seeded_rng;
+
+#[cfg(test)]
+pub mod fixtures;
+
+pub mod circuit_digests;
+
 pub mod copy_of_private_plonky2_functions;
 
+pub mod random_access;
+
 /// measures the time needed to execute an expression.
 ///
 /// It accepts two input formats:
 /// - expr
-/// - expr ; string_literal
+/// - expr ; phase, workflow
 ///
 /// When the input is just an expression, the output is
 /// a tuple containing the evaluation of the expression,
 /// followed by the time taken to evaluate it.
 ///
-/// When the input also contains a string literal,
-/// the literal is used to format an output message.
-/// The format argument should contain just a `{:?}`
-/// entry, used to print the time required to evaluate
-/// the expression.
+/// When the input also contains a `utilities::metrics::Phase` and a
+/// workflow name, the time is recorded as a span of that phase in
+/// `utilities::metrics::global_metrics`, if it is enabled.
 /// In this case, the macro evaluates to the input expression.
 #[macro_export]
 macro_rules! time_it {
     (
         $computation: expr;
-        $output_format: literal
+        $phase: expr, $workflow: expr
     ) => {{
         let (out, time) = $crate::time_it!($computation);
-        println!($output_format, time);
+        $crate::utilities::metrics::global_metrics().record_span($workflow, $phase, time);
         out
     }};
 
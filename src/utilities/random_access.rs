@@ -0,0 +1,49 @@
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+/// Like `CircuitBuilder::random_access`, but `table` can be longer than
+/// the lists supported by a single `RandomAccessGate`, and its length does
+/// not have to be a power of two. The table is padded with zeros up to
+/// the next power of two, so indices past its end access `0`.
+///
+/// When the table is too long, we split it in chunks that fit in a gate,
+/// access each chunk with the low bits of `access_index`, then access the
+/// list of results with the high bits.
+pub fn large_random_access<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    access_index: Target,
+    mut table: Vec<Target>,
+) -> Target {
+    assert!(!table.is_empty());
+
+    // The largest number of bits such that a `RandomAccessGate`
+    // with at least one copy fits in a row.
+    let max_bits = (0..)
+        .take_while(|&bits| {
+            2 + (1 << bits) <= builder.config.num_routed_wires
+                && 2 + (1 << bits) + bits <= builder.config.num_wires
+        })
+        .last()
+        .expect("a gate accessing a single item always fits");
+
+    let bits = table.len().next_power_of_two().trailing_zeros() as usize;
+    let zero = builder.zero();
+    table.resize(1 << bits, zero);
+
+    if bits <= max_bits {
+        return builder.random_access(access_index, table);
+    }
+
+    let index_bits = builder.split_le(access_index, bits);
+    let low_index = builder.le_sum(index_bits[..max_bits].iter());
+    let high_index = builder.le_sum(index_bits[max_bits..].iter());
+
+    let chunk_items = Vec::from_iter(
+        table
+            .chunks(1 << max_bits)
+            .map(|chunk| builder.random_access(low_index, chunk.to_vec())),
+    );
+    large_random_access(builder, high_index, chunk_items)
+}
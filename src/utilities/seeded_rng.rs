@@ -0,0 +1,70 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+/// The environment variable that fixes the seed of `test_rng`.
+pub const SEED_ENV_VAR: &str = "PLONKY2_PLAYGROUND_SEED";
+
+/// A random number generator for tests, whose seed can be fixed to
+/// reproduce a failure.
+///
+/// The seed is read from the `PLONKY2_PLAYGROUND_SEED` environment variable
+/// and, if it is not set, it is chosen at random. If the test panics while
+/// the generator is alive, the seed is reported along with the failure,
+/// so that the run can be replayed with
+///
+/// ``` text
+/// PLONKY2_PLAYGROUND_SEED=<seed> cargo test --release <test name>
+/// ```
+///
+/// Panics if the environment variable is set, but is not a `u64`.
+pub fn test_rng() -> TestRng {
+    let seed = match std::env::var(SEED_ENV_VAR) {
+        Ok(seed) => seed
+            .parse()
+            .unwrap_or_else(|_| panic!("{SEED_ENV_VAR} must be a u64, found {seed:?}")),
+        Err(_) => rand::random(),
+    };
+    log::info!("{SEED_ENV_VAR}={seed}");
+    TestRng {
+        rng: StdRng::seed_from_u64(seed),
+        seed,
+    }
+}
+
+/// The generator returned by `test_rng`.
+pub struct TestRng {
+    rng: StdRng,
+    seed: u64,
+}
+
+impl TestRng {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl Drop for TestRng {
+    fn drop(&mut self) {
+        // The output of a test is only shown when it fails.
+        if std::thread::panicking() {
+            eprintln!("the test failed with {SEED_ENV_VAR}={}", self.seed);
+        }
+    }
+}
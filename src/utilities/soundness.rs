@@ -0,0 +1,114 @@
+use anyhow::{anyhow, ensure};
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{target::Target, witness::PartialWitness},
+    plonk::{circuit_data::CircuitData, config::GenericConfig},
+};
+
+/// Checks that changing the value of any of `targets` in `witness`
+/// does not yield a proof of the same statement.
+///
+/// First, `witness` must yield a valid proof, whose public inputs are the
+/// statement. Then, one target at a time, the value `v` of the target in
+/// `witness` is replaced by `v + 1`, and the mutated witness is given to the
+/// prover. A mutation is rejected if the prover fails or panics, if the
+/// verifier rejects the proof, or if the public inputs of the proof are
+/// different from the original ones: in the last case, the mutation
+/// changed the statement, so the proof is not a soundness issue.
+///
+/// Every target must have a value in `witness`. Passing
+/// `witness.target_values.keys().copied()` as `targets` mutates
+/// all of them.
+///
+/// The circuit should be built with `TestEq` as its `ConnectStrategy`,
+/// so that the mutations that break an equality reach the verifier.
+/// With `CopyConstraint` they make the prover panic, which still counts
+/// as a rejection, but says nothing about the verifier.
+///
+/// Fails with the list of the targets whose mutation was accepted.
+pub fn check_witness_mutations<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    circuit: &CircuitData<F, C, D>,
+    witness: &PartialWitness<F>,
+    targets: impl IntoIterator<Item = Target>,
+) -> anyhow::Result<()> {
+    let proof = circuit.prove(witness.clone())?;
+    let public_inputs = proof.public_inputs.clone();
+    circuit.verify(proof)?;
+
+    let mut accepted_mutations = Vec::new();
+    for target in targets {
+        let mut mutated_witness = witness.clone();
+        let value = mutated_witness
+            .target_values
+            .get_mut(&target)
+            .ok_or_else(|| anyhow!("{target:?} has no value in the witness"))?;
+        *value += F::ONE;
+
+        let proof = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            circuit.prove(mutated_witness)
+        }));
+        if let Ok(Ok(proof)) = proof {
+            if proof.public_inputs == public_inputs && circuit.verify(proof).is_ok() {
+                accepted_mutations.push(target);
+            }
+        }
+    }
+
+    ensure!(
+        accepted_mutations.is_empty(),
+        "the proof is still accepted after mutating {accepted_mutations:?}"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::{
+        field::types::Field,
+        iop::witness::{PartialWitness, WitnessWrite},
+        plonk::{
+            circuit_builder::CircuitBuilder,
+            circuit_data::CircuitConfig,
+            config::{GenericConfig, PoseidonGoldilocksConfig},
+        },
+    };
+
+    use crate::utilities::{connect_strategy::ConnectStrategy, test_connect_gate::StandardTestEq};
+
+    /// Checks that the driver catches an unconstrained target,
+    /// and accepts the constrained ones.
+    #[test]
+    fn test_check_witness_mutations() {
+        const D: usize = 2;
+        type PGConfig = PoseidonGoldilocksConfig;
+        type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+        // We prove the knowledge of `x` and `y` such that `x * y == z`,
+        // where `z` is public. `unused` is not constrained at all.
+        let mut builder =
+            CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+        let [x, y, unused] = [(); 3].map(|_| builder.add_virtual_target());
+        let z = builder.add_virtual_public_input();
+        let product = builder.mul(x, y);
+        StandardTestEq::default().connect(&mut builder, product, z);
+        let circuit = builder.build::<PGConfig>();
+
+        let mut witness = PartialWitness::new();
+        witness.set_target(x, BaseField::from_canonical_u64(3));
+        witness.set_target(y, BaseField::from_canonical_u64(5));
+        witness.set_target(z, BaseField::from_canonical_u64(15));
+        witness.set_target(unused, BaseField::ZERO);
+
+        // Mutating `z` changes the statement into a false one.
+        super::check_witness_mutations(&circuit, &witness, [x, y, z])
+            .expect("a mutation of a constrained target is accepted");
+        let err = super::check_witness_mutations(&circuit, &witness, [x, unused])
+            .expect_err("the mutation of an unconstrained target is rejected");
+        assert!(err.to_string().contains(&format!("{unused:?}")));
+    }
+}
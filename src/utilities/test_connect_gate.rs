@@ -1,6 +1,6 @@
 use plonky2::{
-    field::extension::Extendable,
-    gates::gate::Gate,
+    field::{extension::Extendable, packed::PackedField},
+    gates::{gate::Gate, packed_util::PackedEvaluableBase, util::StridedConstraintConsumer},
     hash::hash_types::RichField,
     iop::{
         ext_target::ExtensionTarget,
@@ -10,11 +10,16 @@ use plonky2::{
     plonk::{
         circuit_builder::CircuitBuilder,
         circuit_data::CommonCircuitData,
-        vars::{EvaluationTargets, EvaluationVars},
+        vars::{
+            EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+            EvaluationVarsBasePacked,
+        },
     },
     util::serialization::{Buffer, IoResult},
 };
 
+use super::connect_strategy::ConnectStrategy;
+
 #[cfg(test)]
 use plonky2::{
     field::types::Field,
@@ -38,7 +43,9 @@ use plonky2::{
 ///
 /// Using this gate instead of `connect` allows the circuit to preserve
 /// the logic structure, and avoids a panic during the proving process.
-#[derive(Debug, Clone, Copy)]
+/// The gate is a `ConnectStrategy`, so it can replace `connect` in the
+/// gadgets of this crate.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct TestEq<const N_OPS: usize>;
 
 impl<F: RichField + Extendable<D>, const D: usize, const N_OPS: usize> Gate<F, D>
@@ -85,6 +92,18 @@ impl<F: RichField + Extendable<D>, const D: usize, const N_OPS: usize> Gate<F, D
         )
     }
 
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
     fn eval_unfiltered_circuit(
         &self,
         builder: &mut CircuitBuilder<F, D>,
@@ -104,6 +123,19 @@ impl<F: RichField + Extendable<D>, const D: usize, const N_OPS: usize> Gate<F, D
     }
 }
 
+impl<F: RichField + Extendable<D>, const D: usize, const N_OPS: usize> PackedEvaluableBase<F, D>
+    for TestEq<N_OPS>
+{
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        yield_constr
+            .many((0..N_OPS).map(|idx| vars.local_wires[2 * idx] - vars.local_wires[2 * idx + 1]));
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct NoOpGenerator;
 
@@ -141,19 +173,25 @@ impl<Field: RichField + Extendable<D>, const D: usize> WitnessGenerator<Field, D
     }
 }
 
-impl<const N_OPS: usize> TestEq<N_OPS> {
-    pub fn connect<Field: RichField + Extendable<D>, const D: usize>(
+impl<const N_OPS: usize> ConnectStrategy for TestEq<N_OPS> {
+    fn connect<Field: RichField + Extendable<D>, const D: usize>(
+        &self,
         builder: &mut CircuitBuilder<Field, D>,
         lhs: Target,
         rhs: Target,
     ) {
-        let (gate_row, op) = builder.find_slot(Self, &[], &[]);
+        let (gate_row, op) = builder.find_slot(*self, &[], &[]);
 
         builder.connect(lhs, Target::wire(gate_row, 2 * op));
         builder.connect(rhs, Target::wire(gate_row, 2 * op + 1));
     }
 }
 
+/// The `TestEq` gate that fits in the routed wires of
+/// `CircuitConfig::standard_recursion_config`. That config has 80 routed
+/// wires, and the gate needs 2 wires per operation.
+pub type StandardTestEq = TestEq<{ 80 / 2 }>;
+
 #[test]
 fn test_test_eq_gate() {
     const D: usize = 2;
@@ -198,3 +236,13 @@ fn test_test_eq_gate() {
         _ => panic!("proof generation with valid witness fails"),
     }
 }
+
+#[test]
+fn test_test_eq_gate_eval_fns() -> anyhow::Result<()> {
+    const D: usize = 2;
+    type PGConfig = PoseidonGoldilocksConfig;
+    type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+    plonky2::gates::gate_testing::test_low_degree::<BaseField, _, D>(TestEq::<3>);
+    plonky2::gates::gate_testing::test_eval_fns::<BaseField, PGConfig, _, D>(TestEq::<3>)
+}
@@ -0,0 +1,80 @@
+use plonky2::{
+    field::extension::Extendable,
+    gates::{
+        arithmetic_base::ArithmeticGate, arithmetic_extension::ArithmeticExtensionGate,
+        base_sum::BaseSumGate, constant::ConstantGate, coset_interpolation::CosetInterpolationGate,
+        exponentiation::ExponentiationGate, gate::GateRef, lookup::LookupGate,
+        lookup_table::LookupTableGate, multiplication_extension::MulExtensionGate, noop::NoopGate,
+        poseidon::PoseidonGate, poseidon_mds::PoseidonMdsGate, public_input::PublicInputGate,
+        random_access::RandomAccessGate, reducing::ReducingGate,
+        reducing_extension::ReducingExtensionGate,
+    },
+    get_gate_tag_impl,
+    hash::hash_types::RichField,
+    plonk::circuit_data::CommonCircuitData,
+    read_gate_impl,
+    util::serialization::{Buffer, GateSerializer, IoResult, Read, Write},
+};
+
+use crate::permutation::PermutationGate;
+
+/// Plonky2's `DefaultGateSerializer` only knows about the gates defined
+/// in plonky2 itself, so it cannot serialize the common data of a circuit
+/// that uses the gates defined in this crate.
+///
+/// This serializer handles all the default gates, plus `PermutationGate`.
+/// New gates must be appended at the end of the list, so that the tags of
+/// the gates that are already there do not change.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaygroundGateSerializer;
+
+// We would like to use plonky2's `impl_gate_serializer` macro here, but in
+// plonky2 0.2 it refers to a private re-export of `Vec`, so we expand it by hand.
+macro_rules! playground_gates {
+    ($macro: ident, $($args: expr),+) => {
+        $macro!(
+            $($args),+,
+            ArithmeticGate,
+            ArithmeticExtensionGate<D>,
+            BaseSumGate<2>,
+            ConstantGate,
+            CosetInterpolationGate<F, D>,
+            ExponentiationGate<F, D>,
+            LookupGate,
+            LookupTableGate,
+            MulExtensionGate<D>,
+            NoopGate,
+            PoseidonMdsGate<F, D>,
+            PoseidonGate<F, D>,
+            PublicInputGate,
+            RandomAccessGate<F, D>,
+            ReducingExtensionGate<D>,
+            ReducingGate<D>,
+            PermutationGate
+        )
+    };
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D>
+    for PlaygroundGateSerializer
+{
+    fn read_gate(
+        &self,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<GateRef<F, D>> {
+        let tag = buf.read_u32()?;
+        playground_gates!(read_gate_impl, buf, tag, common_data)
+    }
+
+    fn write_gate(
+        &self,
+        buf: &mut Vec<u8>,
+        gate: &GateRef<F, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        let tag = playground_gates!(get_gate_tag_impl, gate)?;
+        buf.write_u32(tag)?;
+        gate.0.serialize(buf, common_data)
+    }
+}
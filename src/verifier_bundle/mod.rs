@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use anyhow::{anyhow, ensure, Context};
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    plonk::{
+        circuit_data::{CircuitData, VerifierCircuitData},
+        config::{GenericConfig, Hasher},
+        proof::ProofWithPublicInputs,
+    },
+    util::serialization::{Buffer, Read, Write},
+};
+
+mod gate_serializer;
+pub use gate_serializer::PlaygroundGateSerializer;
+
+#[cfg(test)]
+mod tests;
+
+/// The version of the on-disk format of `VerifierBundle`.
+///
+/// It must be increased every time the layout written by
+/// `VerifierBundle::to_bytes` changes, or the tags used by
+/// `PlaygroundGateSerializer` change.
+pub const VERIFIER_BUNDLE_VERSION: u32 = 1;
+
+/// Every serialized `VerifierBundle` starts with these bytes, so that
+/// we can reject files that are not bundles at all with a meaningful error.
+const VERIFIER_BUNDLE_MAGIC: [u8; 8] = *b"P2PGVRFY";
+
+/// The digest type of the circuits whose configuration is `C`.
+pub type CircuitDigest<F, C, const D: usize> = <<C as GenericConfig<D>>::Hasher as Hasher<F>>::Hash;
+
+/// All the data a verifier needs to check the proofs of a circuit,
+/// without having to build the circuit itself.
+///
+/// The bundle is serialized as
+///
+/// ``` text
+/// magic bytes || version || circuit digest || verifier-only data || common data
+/// ```
+///
+/// The circuit digest is written in the clear, so that one can identify
+/// the circuit a bundle belongs to without parsing the rest of it.
+/// When reading a bundle, we check that it matches the digest contained
+/// in the verifier-only data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierBundle<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+{
+    verifier_data: VerifierCircuitData<F, C, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    VerifierBundle<F, C, D>
+{
+    pub fn new(verifier_data: VerifierCircuitData<F, C, D>) -> Self {
+        Self { verifier_data }
+    }
+
+    /// Extracts the verifier data from the data of a circuit.
+    pub fn from_circuit_data(circuit_data: &CircuitData<F, C, D>) -> Self {
+        Self::new(circuit_data.verifier_data())
+    }
+
+    /// Grants read access to the verifier data stored in the bundle.
+    pub fn verifier_data(&self) -> &VerifierCircuitData<F, C, D> {
+        &self.verifier_data
+    }
+
+    /// Consumes the bundle, returning the verifier data it contains.
+    pub fn into_verifier_data(self) -> VerifierCircuitData<F, C, D> {
+        self.verifier_data
+    }
+
+    /// The digest of the circuit this bundle verifies the proofs of.
+    pub fn circuit_digest(&self) -> CircuitDigest<F, C, D> {
+        self.verifier_data.verifier_only.circuit_digest
+    }
+
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&VERIFIER_BUNDLE_MAGIC);
+        bytes
+            .write_u32(VERIFIER_BUNDLE_VERSION)
+            .and_then(|_| bytes.write_hash::<F, C::Hasher>(self.circuit_digest()))
+            .and_then(|_| {
+                bytes.write_verifier_circuit_data(&self.verifier_data, &PlaygroundGateSerializer)
+            })
+            .map_err(|err| anyhow!("cannot serialize the verifier bundle: {err:?}"))?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        ensure!(
+            bytes.starts_with(&VERIFIER_BUNDLE_MAGIC),
+            "the data is not a verifier bundle"
+        );
+        let mut buffer = Buffer::new(&bytes[VERIFIER_BUNDLE_MAGIC.len()..]);
+
+        let version = buffer
+            .read_u32()
+            .map_err(|_| anyhow!("the verifier bundle is truncated"))?;
+        ensure!(
+            version == VERIFIER_BUNDLE_VERSION,
+            "unsupported verifier bundle version {version}, expected {VERIFIER_BUNDLE_VERSION}"
+        );
+
+        let circuit_digest = buffer
+            .read_hash::<F, C::Hasher>()
+            .map_err(|_| anyhow!("the verifier bundle is truncated"))?;
+        let verifier_data = buffer
+            .read_verifier_circuit_data(&PlaygroundGateSerializer)
+            .map_err(|err| anyhow!("cannot deserialize the verifier data: {err:?}"))?;
+
+        ensure!(
+            buffer.unread_bytes().is_empty(),
+            "the verifier bundle contains trailing bytes"
+        );
+        ensure!(
+            circuit_digest == verifier_data.verifier_only.circuit_digest,
+            "the circuit digest does not match the verifier data"
+        );
+
+        Ok(Self { verifier_data })
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("cannot write the verifier bundle to {}", path.display()))
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("cannot read the verifier bundle {}", path.display()))?;
+        Self::from_bytes(&bytes)
+            .with_context(|| format!("{} is not a valid verifier bundle", path.display()))
+    }
+
+    /// Reads a proof serialized with `ProofWithPublicInputs::to_bytes`,
+    /// using the common data stored in the bundle.
+    pub fn proof_from_bytes(
+        &self,
+        bytes: Vec<u8>,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        ProofWithPublicInputs::from_bytes(bytes, &self.verifier_data.common)
+    }
+}
+
+/// Verifies `proof` against the circuit described by `bundle`.
+///
+/// Unlike `CircuitData::verify`, this only needs the data stored in the
+/// bundle, so it can be used by processes that never build the circuit.
+pub fn verify_with_bundle<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    bundle: &VerifierBundle<F, C, D>,
+    proof: ProofWithPublicInputs<F, C, D>,
+) -> anyhow::Result<()> {
+    bundle.verifier_data.verify(proof)
+}
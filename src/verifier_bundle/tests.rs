@@ -0,0 +1,171 @@
+use plonky2::{
+    field::types::Field,
+    iop::witness::{PartialWitness, WitnessWrite},
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::CircuitConfig,
+        config::{GenericConfig, PoseidonGoldilocksConfig},
+    },
+};
+
+use super::{verify_with_bundle, VerifierBundle, VERIFIER_BUNDLE_MAGIC};
+use crate::permutation::{ApplyPermutation, DefaultSwapSchedule, SwapSchedule};
+
+const D: usize = 2;
+type PGConfig = PoseidonGoldilocksConfig;
+type BaseField = <PGConfig as GenericConfig<D>>::F;
+
+/// Checks that a bundle survives serialization, and that the proofs
+/// of the circuit can be checked using only the deserialized bundle.
+#[test]
+fn test_sudoku_verifier_bundle() {
+    const SIZE: usize = 4;
+    const SIZE_SQRT: usize = 2;
+
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let sudoku_target =
+        crate::sudoku::SudokuCircuitBuilder::<SIZE, SIZE_SQRT>::add_proof_of_sudoku_solution(
+            &mut builder,
+        )
+        .expect("Circuit building goes wrong.");
+    let circuit = builder.build::<PGConfig>();
+
+    let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    let problem = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+    let mut witness = PartialWitness::new();
+    crate::sudoku::SudokuWitnessBuilder::set_sudoku_witness(
+        &mut witness,
+        &sudoku_target,
+        problem,
+        solution,
+    );
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+
+    let bundle_bytes = VerifierBundle::from_circuit_data(&circuit)
+        .to_bytes()
+        .expect("bundle serialization goes wrong");
+    // From now on, we only use the serialized data.
+    drop(circuit);
+
+    let bundle = VerifierBundle::<BaseField, PGConfig, D>::from_bytes(&bundle_bytes)
+        .expect("bundle deserialization goes wrong");
+    let proof = bundle
+        .proof_from_bytes(proof.to_bytes())
+        .expect("proof deserialization goes wrong");
+
+    verify_with_bundle(&bundle, proof).expect("proof verification goes wrong");
+}
+
+#[test]
+fn test_permutation_verifier_bundle() {
+    const N_OBJECTS: usize = 5;
+
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let inputs = builder.add_virtual_public_input_arr::<N_OBJECTS>();
+    let outputs = builder.add_virtual_public_input_arr::<N_OBJECTS>();
+    let selectors =
+        builder.add_virtual_targets(builder.permutation_swap_schedule_length(N_OBJECTS));
+    builder
+        .add_permutation_gate(&inputs, &selectors, &outputs, true)
+        .expect("Circuit building fails while adding the permutation gate.");
+    let circuit = builder.build::<PGConfig>();
+
+    // Applying the swaps selected for `permutation` to `0..N_OBJECTS`
+    // outputs the inverse of `permutation`.
+    let mut permutation = [3, 0, 4, 1, 2];
+    let output_values: [_; N_OBJECTS] = core::array::from_fn(|idx| {
+        let position = permutation.iter().position(|&n| n == idx).unwrap();
+        BaseField::from_canonical_usize(position)
+    });
+    let mut witness = PartialWitness::new();
+    witness.set_target_arr(
+        &inputs,
+        &core::array::from_fn::<_, N_OBJECTS, _>(BaseField::from_canonical_usize),
+    );
+    witness.set_target_arr(&outputs, &output_values);
+    for (target, (selector, _idx1, _idx2)) in
+        selectors
+            .iter()
+            .zip(DefaultSwapSchedule::permutation_to_swap_schedule(
+                &mut permutation,
+            ))
+    {
+        witness.set_target(*target, BaseField::from_bool(selector));
+    }
+    let proof = circuit.prove(witness).expect("proof generation goes wrong");
+
+    let path = std::env::temp_dir().join(format!(
+        "plonky2_playground_permutation_bundle_{}.bin",
+        std::process::id()
+    ));
+    VerifierBundle::from_circuit_data(&circuit)
+        .write_to_file(&path)
+        .expect("cannot write the bundle");
+    let bundle = VerifierBundle::<BaseField, PGConfig, D>::read_from_file(&path)
+        .expect("cannot read the bundle");
+    std::fs::remove_file(&path).expect("cannot remove the bundle file");
+
+    assert_eq!(bundle.verifier_data(), &circuit.verifier_data());
+    verify_with_bundle(&bundle, proof).expect("proof verification goes wrong");
+}
+
+#[test]
+fn test_product_verifier_bundle() {
+    let circuit = crate::factorial::recursive_product_circuit();
+    let proof = circuit.prove(7, BaseField::ONE, BaseField::ONE);
+
+    let bundle = VerifierBundle::<BaseField, PGConfig, D>::from_bytes(
+        &circuit
+            .verifier_bundle()
+            .to_bytes()
+            .expect("bundle serialization goes wrong"),
+    )
+    .expect("bundle deserialization goes wrong");
+
+    assert_eq!(
+        proof.public_inputs[1],
+        BaseField::from_canonical_usize(5040)
+    );
+    verify_with_bundle(&bundle, proof).expect("proof verification goes wrong");
+}
+
+/// Checks that corrupted bundles are rejected when they are read.
+#[test]
+fn test_corrupted_verifier_bundle() {
+    let mut builder =
+        CircuitBuilder::<BaseField, D>::new(CircuitConfig::standard_recursion_config());
+    let target = builder.add_virtual_public_input();
+    builder.range_check(target, 8);
+    let bundle_bytes = VerifierBundle::from_circuit_data(&builder.build::<PGConfig>())
+        .to_bytes()
+        .expect("bundle serialization goes wrong");
+
+    VerifierBundle::<BaseField, PGConfig, D>::from_bytes(&bundle_bytes)
+        .expect("the original bundle should be valid");
+
+    let version_offset = VERIFIER_BUNDLE_MAGIC.len();
+    let digest_offset = version_offset + 4;
+
+    let mut wrong_magic = bundle_bytes.clone();
+    wrong_magic[0] ^= 1;
+    let mut wrong_version = bundle_bytes.clone();
+    wrong_version[version_offset] ^= 1;
+    let mut wrong_digest = bundle_bytes.clone();
+    wrong_digest[digest_offset] ^= 1;
+    let mut trailing_bytes = bundle_bytes.clone();
+    trailing_bytes.push(0);
+    let truncated = &bundle_bytes[..bundle_bytes.len() - 1];
+
+    for corrupted in [
+        &wrong_magic[..],
+        &wrong_version,
+        &wrong_digest,
+        &trailing_bytes,
+        truncated,
+    ] {
+        VerifierBundle::<BaseField, PGConfig, D>::from_bytes(corrupted)
+            .expect_err("a corrupted bundle is accepted");
+    }
+}